@@ -0,0 +1,530 @@
+use std::convert::TryInto;
+use std::iter;
+use std::marker::PhantomData;
+
+use crate::{ErrorKind, Expected, Input, ParseError, ParseResult, Parser};
+
+
+/// Byte order of a multi-byte integer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endian {
+    Big,
+    Little,
+}
+
+
+/// An integer type that can be read from a fixed number of bytes.
+pub trait FixedWidth: Sized {
+    /// How many bytes the integer takes up.
+    const WIDTH: usize;
+
+    /// Builds the integer from exactly `WIDTH` bytes in the given order.
+    fn from_bytes(bytes: &[u8], endian: Endian) -> Self;
+}
+
+
+macro_rules! fixed_width {
+    ($($int:ty)+) => {
+        $(
+            impl FixedWidth for $int {
+                const WIDTH: usize = std::mem::size_of::<$int>();
+
+                fn from_bytes(bytes: &[u8], endian: Endian) -> Self {
+                    let bytes = bytes.try_into().expect("wrong number of bytes for integer");
+                    match endian {
+                        Endian::Big => <$int>::from_be_bytes(bytes),
+                        Endian::Little => <$int>::from_le_bytes(bytes),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+
+fixed_width!(u8 u16 u32 u64 u128 i8 i16 i32 i64 i128);
+
+
+// Reads exactly `n` tokens, failing where the input runs out.
+fn take_exactly<I: Input>(input: &I, n: usize) -> Result<I, <I as Input>::Error> {
+    let mut rest = input.clone();
+    for _ in 0..n {
+        match rest.next_token() {
+            Some((_, next)) => rest = next,
+            None => return Err(ParseError::from_unexpected(&rest, Some(Expected::Any))),
+        }
+    }
+    Ok(rest)
+}
+
+
+/// A fixed-width integer stored in `endian` byte order.
+pub struct Int<T, I> {
+    endian: Endian,
+    phantom: PhantomData<(T, I)>
+}
+
+
+impl<T, I> Clone for Int<T, I> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+
+impl<T, I> Copy for Int<T, I> {}
+
+
+impl<T, I> Int<T, I> {
+    pub fn new(endian: Endian) -> Self {
+        Self {
+            endian,
+            phantom: PhantomData
+        }
+    }
+}
+
+
+impl<T, I> Parser<I> for Int<T, I>
+where
+    T: FixedWidth,
+    I: Input<Token = u8>
+{
+    type Out = T;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let mut bytes = [0; 16];
+        let mut rest = input;
+        for byte in &mut bytes[..T::WIDTH] {
+            match rest.next_token() {
+                Some((b, next)) => {
+                    *byte = b;
+                    rest = next;
+                }
+                None => return Err(ParseError::from_unexpected(&rest, Some(Expected::Any))),
+            }
+        }
+
+        Ok((T::from_bytes(&bytes[..T::WIDTH], self.endian), rest))
+    }
+}
+
+
+macro_rules! int_parsers {
+    ($($name:ident: $int:ty, $endian:ident;)+) => {
+        $(
+            pub fn $name<I>() -> Int<$int, I> {
+                Int::new(Endian::$endian)
+            }
+        )+
+    };
+}
+
+
+int_parsers! {
+    be_u16: u16, Big;
+    be_u32: u32, Big;
+    be_u64: u64, Big;
+    be_i16: i16, Big;
+    be_i32: i32, Big;
+    be_i64: i64, Big;
+    le_u16: u16, Little;
+    le_u32: u32, Little;
+    le_u64: u64, Little;
+    le_i16: i16, Little;
+    le_i32: i32, Little;
+    le_i64: i64, Little;
+}
+
+
+/// An unsigned LEB128 variable-length integer.
+///
+/// Fails with [`ErrorKind::Overflow`](crate::ErrorKind::Overflow) if the
+/// encoded value does not fit in a `u64`.
+pub struct Uleb128<I> {
+    phantom: PhantomData<I>
+}
+
+
+impl<I> Clone for Uleb128<I> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+
+impl<I> Copy for Uleb128<I> {}
+
+
+impl<I: Input<Token = u8>> Parser<I> for Uleb128<I> {
+    type Out = u64;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let mut result = 0;
+        let mut shift = 0;
+        let mut rest = input.clone();
+        loop {
+            let (byte, next) = match rest.next_token() {
+                Some(ok) => ok,
+                None => return Err(ParseError::from_unexpected(&rest, Some(Expected::Any))),
+            };
+            if shift >= 64 || (shift == 63 && byte & 0x7f > 1) {
+                return Err(ParseError::from_kind(&input, ErrorKind::Overflow));
+            }
+
+            result |= u64::from(byte & 0x7f) << shift;
+            shift += 7;
+            rest = next;
+            if byte & 0x80 == 0 {
+                return Ok((result, rest));
+            }
+        }
+    }
+}
+
+
+pub fn uleb128<I>() -> Uleb128<I> {
+    Uleb128 { phantom: PhantomData }
+}
+
+
+/// A signed LEB128 variable-length integer.
+///
+/// Fails with [`ErrorKind::Overflow`](crate::ErrorKind::Overflow) if the
+/// encoded value does not fit in an `i64`.
+pub struct Sleb128<I> {
+    phantom: PhantomData<I>
+}
+
+
+impl<I> Clone for Sleb128<I> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+
+impl<I> Copy for Sleb128<I> {}
+
+
+impl<I: Input<Token = u8>> Parser<I> for Sleb128<I> {
+    type Out = i64;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let mut result = 0;
+        let mut shift = 0;
+        let mut rest = input.clone();
+        loop {
+            let (byte, next) = match rest.next_token() {
+                Some(ok) => ok,
+                None => return Err(ParseError::from_unexpected(&rest, Some(Expected::Any))),
+            };
+            // The last byte may only carry the sign bit and its extension.
+            if shift >= 64 || (shift == 63 && byte & 0x7f != 0 && byte & 0x7f != 0x7f) {
+                return Err(ParseError::from_kind(&input, ErrorKind::Overflow));
+            }
+
+            result |= i64::from(byte & 0x7f) << shift;
+            shift += 7;
+            rest = next;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    result |= -1 << shift;
+                }
+                return Ok((result, rest));
+            }
+        }
+    }
+}
+
+
+pub fn sleb128<I>() -> Sleb128<I> {
+    Sleb128 { phantom: PhantomData }
+}
+
+
+/// Exactly `n` bytes, returned as a slice of the input.
+pub struct TakeBytes<I> {
+    n: usize,
+    phantom: PhantomData<I>
+}
+
+
+impl<I> Clone for TakeBytes<I> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+
+impl<I> Copy for TakeBytes<I> {}
+
+
+impl<I: Input<Token = u8>> Parser<I> for TakeBytes<I> {
+    type Out = <I as Input>::Slice;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let rest = take_exactly(&input, self.n)?;
+        Ok((input.slice_to(&rest), rest))
+    }
+}
+
+
+pub fn take_bytes<I>(n: usize) -> TakeBytes<I> {
+    TakeBytes { n, phantom: PhantomData }
+}
+
+
+/// A blob whose length in bytes is given by `length`, returned as a slice
+/// of the input, e.g. `length_prefixed(be_u32())`.
+///
+/// Fails with [`ErrorKind::Overflow`](crate::ErrorKind::Overflow) if the
+/// length does not fit in a `usize`.
+#[derive(Clone)]
+pub struct LengthPrefixed<L> {
+    length: L
+}
+
+
+impl<I, L> Parser<I> for LengthPrefixed<L>
+where
+    I: Input<Token = u8>,
+    L: Parser<I>,
+    <L as Parser<I>>::Out: TryInto<usize>
+{
+    type Out = <I as Input>::Slice;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let (len, start) = self.length.call(input.clone())?;
+        let len = len.try_into().map_err(|_| ParseError::from_kind(&input, ErrorKind::Overflow))?;
+        let rest = take_exactly(&start, len)?;
+        Ok((start.slice_to(&rest), rest))
+    }
+}
+
+
+pub fn length_prefixed<L>(length: L) -> LengthPrefixed<L> {
+    LengthPrefixed { length }
+}
+
+
+/// Exactly the bytes of `magic`, such as a file signature, returned as a
+/// slice of the input.
+pub struct Tag<S, I> {
+    magic: S,
+    phantom: PhantomData<I>
+}
+
+
+impl<S: Clone, I> Clone for Tag<S, I> {
+    fn clone(&self) -> Self {
+        Self {
+            magic: self.magic.clone(),
+            phantom: PhantomData
+        }
+    }
+}
+
+
+impl<S: Copy, I> Copy for Tag<S, I> {}
+
+
+impl<S, I> Parser<I> for Tag<S, I>
+where
+    I: Input<Token = u8>,
+    S: AsRef<[u8]>
+{
+    type Out = <I as Input>::Slice;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let magic = self.magic.as_ref();
+        let mut rest = input.clone();
+        for &expected in magic {
+            match rest.next_token() {
+                Some((b, next)) if b == expected => rest = next,
+                _ => {
+                    let expected = iter::once_with(|| Expected::Literal(magic.to_vec()));
+                    return Err(ParseError::from_unexpected(&input, expected));
+                }
+            }
+        }
+
+        Ok((input.slice_to(&rest), rest))
+    }
+}
+
+
+pub fn tag<S: AsRef<[u8]>, I>(magic: S) -> Tag<S, I> {
+    Tag { magic, phantom: PhantomData }
+}
+
+
+/// Skips padding up to the next offset that is a multiple of `alignment`.
+///
+/// Offsets are counted from the start of the source, so this consumes
+/// nothing if the input is already aligned.
+pub struct Align<I> {
+    alignment: usize,
+    phantom: PhantomData<I>
+}
+
+
+impl<I> Clone for Align<I> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+
+impl<I> Copy for Align<I> {}
+
+
+impl<I: Input<Token = u8>> Parser<I> for Align<I> {
+    type Out = ();
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let padding = (self.alignment - input.offset() % self.alignment) % self.alignment;
+        let rest = take_exactly(&input, padding)?;
+        Ok(((), rest))
+    }
+}
+
+
+/// # Panics
+///
+/// Panics if `alignment` is zero.
+pub fn align<I>(alignment: usize) -> Align<I> {
+    assert!(alignment > 0, "alignment must be positive");
+    Align { alignment, phantom: PhantomData }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bytes, ErrorKind, Item};
+
+    #[test]
+    fn int_test() {
+        let input = Bytes::new(&[0x12, 0x34, 0x56, 0x78]);
+
+        assert_eq!(0x1234_5678, be_u32().call(input).unwrap().0);
+        assert_eq!(0x7856_3412, le_u32().call(input).unwrap().0);
+        assert_eq!(0x3412, le_u16().call(input).unwrap().0);
+        assert_eq!(-2, be_i16().call(Bytes::new(&[0xff, 0xfe])).unwrap().0);
+    }
+
+    #[test]
+    fn int_truncated_test() {
+        let err = be_u32().call(Bytes::new(&[0x00, 0x01])).unwrap_err();
+
+        assert_eq!(2, err.offset());
+        assert_eq!(&[Expected::Any], err.expected());
+    }
+
+    #[test]
+    fn uleb128_test() {
+        let (result, rest) =
+            uleb128()
+                .call(Bytes::new(&[0xe5, 0x8e, 0x26, 0xff]))
+                .unwrap();
+
+        assert_eq!(624_485, result);
+        assert_eq!(&[0xff], rest.as_slice());
+
+        let max = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        assert_eq!(u64::MAX, uleb128().call(Bytes::new(&max)).unwrap().0);
+
+        let too_big = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02];
+        let err = uleb128().call(Bytes::new(&too_big)).unwrap_err();
+        assert_eq!(ErrorKind::Overflow, err.kind());
+        assert_eq!(0, err.offset());
+    }
+
+    #[test]
+    fn sleb128_test() {
+        assert_eq!(-123_456, sleb128().call(Bytes::new(&[0xc0, 0xbb, 0x78])).unwrap().0);
+        assert_eq!(63, sleb128().call(Bytes::new(&[0x3f])).unwrap().0);
+        assert_eq!(-64, sleb128().call(Bytes::new(&[0x40])).unwrap().0);
+
+        let min = [0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7f];
+        assert_eq!(i64::MIN, sleb128().call(Bytes::new(&min)).unwrap().0);
+
+        let err = sleb128().call(Bytes::new(&[0x80, 0x80])).unwrap_err();
+        assert_eq!(2, err.offset());
+    }
+
+    #[test]
+    fn tag_test() {
+        let (result, rest) = tag(b"\x89PNG").call(Bytes::new(b"\x89PNG\r\n")).unwrap();
+
+        assert_eq!(b"\x89PNG", result);
+        assert_eq!(b"\r\n", rest.as_slice());
+
+        let err = tag(b"\x89PNG").call(Bytes::new(b"GIF89a")).unwrap_err();
+        assert_eq!(&[Expected::Literal(b"\x89PNG".to_vec())], err.expected());
+        assert_eq!(0, err.offset());
+    }
+
+    #[test]
+    fn length_prefixed_test() {
+        let (result, rest) =
+            length_prefixed(Item::new())
+                .call(Bytes::new(&[3, 0xa, 0xb, 0xc, 0xd]))
+                .unwrap();
+
+        assert_eq!(&[0xa, 0xb, 0xc], result);
+        assert_eq!(&[0xd], rest.as_slice());
+
+        let err = length_prefixed(Item::new()).call(Bytes::new(&[3, 0xa])).unwrap_err();
+        assert_eq!(2, err.offset());
+    }
+
+    #[test]
+    fn align_test() {
+        let (_, rest) = Item::new().call(Bytes::new(&[1, 0, 0, 0, 2])).unwrap();
+
+        let (_, rest) = align(4).call(rest).unwrap();
+        assert_eq!(4, rest.offset());
+
+        let (_, rest) = align(4).call(rest).unwrap();
+        assert_eq!(4, rest.offset());
+
+        let (_, rest) = Item::new().call(rest).unwrap();
+        assert!(align(4).call(rest).is_err());
+    }
+
+    #[test]
+    fn png_chunk_test() {
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x02IHDRab\x00\x00\x00\x2a\x00\x00\x00\x00IEND\xae\x42\x60\x82";
+
+        let chunk =
+            be_u32()
+                .bind(|len| (take_bytes(4), take_bytes(len as usize), be_u32()));
+
+        let (chunks, rest) =
+            tag(b"\x89PNG\r\n\x1a\n")
+                .right(chunk.many::<Vec<_>>())
+                .call(Bytes::new(png))
+                .unwrap();
+
+        assert_eq!(
+            vec![(&b"IHDR"[..], &b"ab"[..], 42), (&b"IEND"[..], &b""[..], 0xae42_6082)],
+            chunks
+        );
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn tlv_test() {
+        let tlv = (Item::new(), length_prefixed(uleb128()));
+
+        let (records, _) =
+            tlv
+                .many::<Vec<_>>()
+                .call(Bytes::new(&[1, 2, b'h', b'i', 7, 0, 2, 1, 0xff]))
+                .unwrap();
+
+        assert_eq!(vec![(1, &b"hi"[..]), (7, &b""[..]), (2, &[0xff][..])], records);
+    }
+}
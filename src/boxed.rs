@@ -0,0 +1,223 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::{Input, ParseResult, Parser};
+
+
+impl<I: Input, P: Parser<I> + ?Sized> Parser<I> for &P {
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        (**self).call(input)
+    }
+}
+
+
+impl<I: Input, P: Parser<I> + ?Sized> Parser<I> for Box<P> {
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        (**self).call(input)
+    }
+}
+
+
+impl<I: Input, P: Parser<I> + ?Sized> Parser<I> for Rc<P> {
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        (**self).call(input)
+    }
+}
+
+
+impl<I: Input, P: Parser<I> + ?Sized> Parser<I> for Arc<P> {
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        (**self).call(input)
+    }
+}
+
+
+/// A parser whose type has been erased, built with
+/// [`Parser::boxed`](crate::Parser::boxed).
+///
+/// Every parser with the same input and output boxes to the same type, so
+/// differently built parsers can be returned from one function or stored
+/// together in a collection. Cloning only bumps a reference count. Any
+/// parser can be boxed, which leaves the result tied to its thread; see
+/// [`SyncBoxedParser`] for one that can be shared between threads.
+pub struct BoxedParser<'a, I, O> {
+    parser: Rc<dyn Parser<I, Out = O> + 'a>
+}
+
+
+impl<'a, I, O> BoxedParser<'a, I, O> {
+    pub fn new<P>(parser: P) -> Self
+    where
+        I: Input,
+        P: Parser<I, Out = O> + 'a
+    {
+        Self { parser: Rc::new(parser) }
+    }
+}
+
+
+impl<'a, I, O> Clone for BoxedParser<'a, I, O> {
+    fn clone(&self) -> Self {
+        Self { parser: Rc::clone(&self.parser) }
+    }
+}
+
+
+impl<'a, I: Input, O> Parser<I> for BoxedParser<'a, I, O> {
+    type Out = O;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        self.parser.call(input)
+    }
+
+    fn boxed<'b>(self) -> BoxedParser<'b, I, Self::Out>
+    where
+        Self: Sized + 'b
+    {
+        // Already erased; boxing again would only add an indirection.
+        BoxedParser { parser: self.parser }
+    }
+}
+
+
+/// A [`BoxedParser`] that is `Send + Sync`, built with
+/// [`Parser::boxed_sync`](crate::Parser::boxed_sync), so that a grammar
+/// built once can be shared between threads.
+///
+/// Only parsers that are `Send + Sync` themselves can be boxed this way.
+pub struct SyncBoxedParser<'a, I, O> {
+    parser: Arc<dyn Parser<I, Out = O> + Send + Sync + 'a>
+}
+
+
+impl<'a, I, O> SyncBoxedParser<'a, I, O> {
+    pub fn new<P>(parser: P) -> Self
+    where
+        I: Input,
+        P: Parser<I, Out = O> + Send + Sync + 'a
+    {
+        Self { parser: Arc::new(parser) }
+    }
+}
+
+
+impl<'a, I, O> Clone for SyncBoxedParser<'a, I, O> {
+    fn clone(&self) -> Self {
+        Self { parser: Arc::clone(&self.parser) }
+    }
+}
+
+
+impl<'a, I: Input, O> Parser<I> for SyncBoxedParser<'a, I, O> {
+    type Out = O;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        self.parser.call(input)
+    }
+
+    fn boxed_sync<'b>(self) -> SyncBoxedParser<'b, I, Self::Out>
+    where
+        Self: Sized + Send + Sync + 'b
+    {
+        SyncBoxedParser { parser: self.parser }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{char, string, take_while1, Item, Text};
+    use std::cell::Cell;
+    use std::collections::HashMap;
+
+    fn sign<'a>(allow_plus: bool) -> BoxedParser<'a, Text<'a>, char> {
+        if allow_plus {
+            char('+').or(char('-')).boxed()
+        } else {
+            char('-').boxed()
+        }
+    }
+
+    #[test]
+    fn boxed_test() {
+        let (result, rest) =
+            Item::new()
+                .take(2)
+                .boxed()
+                .map(|chars| chars.len())
+                .call(Text::new("abc"))
+                .unwrap();
+
+        assert_eq!(2, result);
+        assert_eq!("c", rest.as_str());
+    }
+
+    #[test]
+    fn boxed_branches_test() {
+        assert!(sign(true).call(Text::new("+1")).is_ok());
+        assert!(sign(false).call(Text::new("+1")).is_err());
+        assert!(sign(false).call(Text::new("-1")).is_ok());
+    }
+
+    #[test]
+    fn grammar_table_test() {
+        let mut rules: HashMap<&str, BoxedParser<Text, &str>> = HashMap::new();
+        rules.insert("keyword", string("let").or(string("fn")).boxed());
+        rules.insert("number", take_while1(|c| c.is_ascii_digit()).boxed());
+
+        let (result, _) = rules["keyword"].call(Text::new("fn main")).unwrap();
+        assert_eq!("fn", result);
+
+        let (result, _) = rules["number"].call(Text::new("42;")).unwrap();
+        assert_eq!("42", result);
+
+        let number = rules["number"].clone();
+        assert!(number.call(Text::new("x")).is_err());
+    }
+
+    #[test]
+    fn box_and_rc_test() {
+        let boxed: Box<dyn Parser<Text, Out = char>> = Box::new(char('a'));
+        let shared = Rc::new(char('b'));
+
+        let (result, _) = boxed.then(shared).call(Text::new("ab")).unwrap();
+
+        assert_eq!(('a', 'b'), result);
+    }
+
+    #[test]
+    fn boxed_local_test() {
+        let seen = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&seen);
+
+        let parser = char('a')
+            .map(move |c| {
+                counter.set(counter.get() + 1);
+                c
+            })
+            .boxed();
+
+        assert!(parser.call(Text::new("a")).is_ok());
+        assert_eq!(1, seen.get());
+    }
+
+    #[test]
+    fn boxed_sync_test() {
+        fn shareable<T: Send + Sync>(_: &T) {}
+
+        let parser = char('a').then(char('b')).boxed_sync();
+        shareable(&parser);
+
+        let (result, _) = parser.clone().boxed_sync().call(Text::new("ab")).unwrap();
+        assert_eq!(('a', 'b'), result);
+    }
+}
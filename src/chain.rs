@@ -0,0 +1,231 @@
+use crate::{Input, Many, ParseResult, Parser, Then};
+
+
+/// One or more `operand`s separated by binary operators, combined from the
+/// left: `1 - 2 - 3` is `(1 - 2) - 3`.
+///
+/// Each `op` yields the function that combines the operands on either
+/// side of it. A trailing operator with no operand after it is left
+/// unconsumed, the same way [`sep_by`](crate::sep_by) treats separators.
+#[derive(Clone)]
+pub struct ChainL1<P, O> {
+    operand: P,
+    op: O
+}
+
+
+impl<I: Input, P, O, F> Parser<I> for ChainL1<P, O>
+where
+    P: Parser<I>,
+    O: Parser<I, Out = F>,
+    F: Fn(<P as Parser<I>>::Out, <P as Parser<I>>::Out) -> <P as Parser<I>>::Out
+{
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let (first, rest) = self.operand.call(input)?;
+        let (tail, rest) = Many::<_, Vec<_>>::new(Then::new(&self.op, &self.operand), 0).call(rest)?;
+        let out = tail.into_iter().fold(first, |left, (f, right)| f(left, right));
+        Ok((out, rest))
+    }
+}
+
+
+pub fn chainl1<P, O>(operand: P, op: O) -> ChainL1<P, O> {
+    ChainL1 { operand, op }
+}
+
+
+/// One or more `operand`s separated by binary operators, combined from the
+/// right: `2 ^ 3 ^ 2` is `2 ^ (3 ^ 2)`.
+///
+/// See [`ChainL1`] for the role of `op`.
+#[derive(Clone)]
+pub struct ChainR1<P, O> {
+    operand: P,
+    op: O
+}
+
+
+impl<I: Input, P, O, F> Parser<I> for ChainR1<P, O>
+where
+    P: Parser<I>,
+    O: Parser<I, Out = F>,
+    F: Fn(<P as Parser<I>>::Out, <P as Parser<I>>::Out) -> <P as Parser<I>>::Out
+{
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let (first, rest) = self.operand.call(input)?;
+        let (tail, rest) = Many::<_, Vec<_>>::new(Then::new(&self.op, &self.operand), 0).call(rest)?;
+
+        // Pair each operand with the operator to its right, then fold from
+        // the last operand back to the first.
+        let mut left = first;
+        let mut pending = Vec::with_capacity(tail.len());
+        for (f, right) in tail {
+            pending.push((left, f));
+            left = right;
+        }
+        let out = pending.into_iter().rev().fold(left, |right, (left, f)| f(left, right));
+        Ok((out, rest))
+    }
+}
+
+
+pub fn chainr1<P, O>(operand: P, op: O) -> ChainR1<P, O> {
+    ChainR1 { operand, op }
+}
+
+
+/// Any number of prefix operators followed by `operand`, applied from the
+/// inside out: `--x` is `-(-x)`.
+///
+/// Each `op` yields the function to apply to the operand after it.
+#[derive(Clone)]
+pub struct Prefix<O, P> {
+    op: O,
+    operand: P
+}
+
+
+impl<I: Input, O, P, F> Parser<I> for Prefix<O, P>
+where
+    P: Parser<I>,
+    O: Parser<I, Out = F>,
+    F: Fn(<P as Parser<I>>::Out) -> <P as Parser<I>>::Out
+{
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let (ops, rest) = Many::<_, Vec<_>>::new(&self.op, 0).call(input)?;
+        let (operand, rest) = self.operand.call(rest)?;
+        let out = ops.into_iter().rev().fold(operand, |x, f| f(x));
+        Ok((out, rest))
+    }
+}
+
+
+pub fn prefix<O, P>(op: O, operand: P) -> Prefix<O, P> {
+    Prefix { op, operand }
+}
+
+
+/// `operand` followed by any number of postfix operators, applied from
+/// the inside out: `x!!` is `(x!)!`.
+///
+/// Each `op` yields the function to apply to the operand before it.
+#[derive(Clone)]
+pub struct Postfix<P, O> {
+    operand: P,
+    op: O
+}
+
+
+impl<I: Input, P, O, F> Parser<I> for Postfix<P, O>
+where
+    P: Parser<I>,
+    O: Parser<I, Out = F>,
+    F: Fn(<P as Parser<I>>::Out) -> <P as Parser<I>>::Out
+{
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let (operand, rest) = self.operand.call(input)?;
+        let (ops, rest) = Many::<_, Vec<_>>::new(&self.op, 0).call(rest)?;
+        let out = ops.into_iter().fold(operand, |x, f| f(x));
+        Ok((out, rest))
+    }
+}
+
+
+pub fn postfix<P, O>(operand: P, op: O) -> Postfix<P, O> {
+    Postfix { operand, op }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{char, one_of, satisfy, Text};
+
+    type Binary = fn(String, String) -> String;
+
+    fn atom<'a>() -> impl Parser<Text<'a>, Out = String> + Clone {
+        satisfy(|c| c.is_ascii_alphanumeric()).map(String::from)
+    }
+
+    fn binary<'a>(ops: &'static str) -> impl Parser<Text<'a>, Out = Binary> + Clone {
+        one_of(ops).map(|op| -> Binary {
+            match op {
+                '-' => |a, b| format!("({}-{})", a, b),
+                '^' => |a, b| format!("({}^{})", a, b),
+                _ => |a, b| format!("({}+{})", a, b),
+            }
+        })
+    }
+
+    #[test]
+    fn chainl1_test() {
+        let (result, rest) =
+            chainl1(atom(), binary("+-"))
+                .call(Text::new("1-2+3-;"))
+                .unwrap();
+
+        assert_eq!("((1-2)+3)", result);
+        assert_eq!("-;", rest.as_str());
+    }
+
+    #[test]
+    fn chainr1_test() {
+        let (result, rest) =
+            chainr1(atom(), binary("^"))
+                .call(Text::new("2^3^2"))
+                .unwrap();
+
+        assert_eq!("(2^(3^2))", result);
+        assert!(rest.is_empty());
+
+        let (single, _) = chainr1(atom(), binary("^")).call(Text::new("x")).unwrap();
+        assert_eq!("x", single);
+    }
+
+    #[test]
+    fn chain_error_test() {
+        let err = chainl1(atom(), binary("+")).call(Text::new("+1")).unwrap_err();
+
+        assert_eq!(0, err.offset());
+    }
+
+    #[test]
+    fn prefix_postfix_test() {
+        let neg = char('-').map(|_| |x: i64| -x);
+        let number = satisfy(|c| c.is_ascii_digit()).map(|c| i64::from(c.to_digit(10).unwrap()));
+
+        let negated = prefix(neg, number.clone());
+
+        let (result, _) = negated.call(Text::new("--7")).unwrap();
+        assert_eq!(7, result);
+
+        let (result, _) = negated.call(Text::new("-7")).unwrap();
+        assert_eq!(-7, result);
+
+        let fact = char('!').map(|_| |n: i64| (1..=n).product::<i64>());
+        let (result, rest) = postfix(number, fact).call(Text::new("3!!?")).unwrap();
+        assert_eq!(720, result);
+        assert_eq!("?", rest.as_str());
+    }
+
+    #[test]
+    fn boolean_test() {
+        // or = and ('|' and)*, and = not ('&' not)*, not = '!'* ('t' | 'f')
+        let value = char('t').map(|_| true).or(char('f').map(|_| false));
+        let not = prefix(char('!').map(|_| |b: bool| !b), value);
+        let and = chainl1(not, char('&').map(|_| |a, b| a && b));
+        let or = chainl1(and, char('|').map(|_| |a, b| a || b));
+
+        assert_eq!(Ok(true), or.call(Text::new("f&f|t")).map(|(b, _)| b));
+        assert_eq!(Ok(false), or.call(Text::new("!t|t&f")).map(|(b, _)| b));
+        assert_eq!(Ok(true), or.call(Text::new("!!t&!f")).map(|(b, _)| b));
+    }
+}
@@ -0,0 +1,503 @@
+use std::iter;
+use std::marker::PhantomData;
+
+use crate::{Expected, Input, ParseError, ParseResult, Parser};
+
+
+/// A single character for which `pred` holds.
+pub struct Satisfy<F, I> {
+    pred: F,
+    input: PhantomData<I>
+}
+
+
+impl<F: Clone, I> Clone for Satisfy<F, I> {
+    fn clone(&self) -> Self {
+        Self {
+            pred: self.pred.clone(),
+            input: PhantomData
+        }
+    }
+}
+
+
+impl<F: Copy, I> Copy for Satisfy<F, I> {}
+
+
+impl<F: Fn(char) -> bool, I> Satisfy<F, I> {
+    pub fn new(pred: F) -> Self {
+        Self { pred, input: PhantomData }
+    }
+}
+
+
+impl<F, I> Parser<I> for Satisfy<F, I>
+where
+    I: Input<Token = char>,
+    F: Fn(char) -> bool
+{
+    type Out = char;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        match input.next_token() {
+            Some((c, rest)) if (self.pred)(c) => Ok((c, rest)),
+            _ => Err(ParseError::from_unexpected(&input, None)),
+        }
+    }
+}
+
+
+pub fn satisfy<F: Fn(char) -> bool, I>(pred: F) -> Satisfy<F, I> {
+    Satisfy::new(pred)
+}
+
+
+/// Exactly the character `c`.
+pub struct Char<I> {
+    c: char,
+    input: PhantomData<I>
+}
+
+
+impl<I> Clone for Char<I> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+
+impl<I> Copy for Char<I> {}
+
+
+impl<I: Input<Token = char>> Parser<I> for Char<I> {
+    type Out = char;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        match input.next_token() {
+            Some((c, rest)) if c == self.c => Ok((c, rest)),
+            _ => Err(ParseError::from_unexpected(&input, Some(Expected::Token(self.c)))),
+        }
+    }
+}
+
+
+pub fn char<I>(c: char) -> Char<I> {
+    Char { c, input: PhantomData }
+}
+
+
+/// Any one of the characters in `chars`.
+pub struct OneOf<S, I> {
+    chars: S,
+    input: PhantomData<I>
+}
+
+
+impl<S: Clone, I> Clone for OneOf<S, I> {
+    fn clone(&self) -> Self {
+        Self {
+            chars: self.chars.clone(),
+            input: PhantomData
+        }
+    }
+}
+
+
+impl<S: Copy, I> Copy for OneOf<S, I> {}
+
+
+impl<S, I> Parser<I> for OneOf<S, I>
+where
+    I: Input<Token = char>,
+    S: AsRef<str>
+{
+    type Out = char;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let chars = self.chars.as_ref();
+        match input.next_token() {
+            Some((c, rest)) if chars.contains(c) => Ok((c, rest)),
+            _ => Err(ParseError::from_unexpected(&input, chars.chars().map(Expected::Token))),
+        }
+    }
+}
+
+
+pub fn one_of<S: AsRef<str>, I>(chars: S) -> OneOf<S, I> {
+    OneOf { chars, input: PhantomData }
+}
+
+
+/// Any character that is not in `chars`.
+pub struct NoneOf<S, I> {
+    chars: S,
+    input: PhantomData<I>
+}
+
+
+impl<S: Clone, I> Clone for NoneOf<S, I> {
+    fn clone(&self) -> Self {
+        Self {
+            chars: self.chars.clone(),
+            input: PhantomData
+        }
+    }
+}
+
+
+impl<S: Copy, I> Copy for NoneOf<S, I> {}
+
+
+impl<S, I> Parser<I> for NoneOf<S, I>
+where
+    I: Input<Token = char>,
+    S: AsRef<str>
+{
+    type Out = char;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        match input.next_token() {
+            Some((c, rest)) if !self.chars.as_ref().contains(c) => Ok((c, rest)),
+            Some(_) => Err(ParseError::from_unexpected(&input, None)),
+            None => Err(ParseError::from_unexpected(&input, Some(Expected::Any))),
+        }
+    }
+}
+
+
+pub fn none_of<S: AsRef<str>, I>(chars: S) -> NoneOf<S, I> {
+    NoneOf { chars, input: PhantomData }
+}
+
+
+/// Exactly the text `literal`, returned as a slice of the input.
+pub struct Literal<S, I> {
+    literal: S,
+    input: PhantomData<I>
+}
+
+
+impl<S: Clone, I> Clone for Literal<S, I> {
+    fn clone(&self) -> Self {
+        Self {
+            literal: self.literal.clone(),
+            input: PhantomData
+        }
+    }
+}
+
+
+impl<S: Copy, I> Copy for Literal<S, I> {}
+
+
+impl<S, I> Parser<I> for Literal<S, I>
+where
+    I: Input<Token = char>,
+    S: AsRef<str>
+{
+    type Out = <I as Input>::Slice;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let literal = self.literal.as_ref();
+        match strip_literal(&input, literal) {
+            Some(rest) => Ok((input.slice_to(&rest), rest)),
+            None => {
+                let expected = iter::once_with(|| Expected::Literal(literal.chars().collect()));
+                Err(ParseError::from_unexpected(&input, expected))
+            }
+        }
+    }
+}
+
+
+// The input after `literal`, if `input` starts with it.
+fn strip_literal<I: Input<Token = char>>(input: &I, literal: &str) -> Option<I> {
+    let mut rest = input.clone();
+    for expected in literal.chars() {
+        match rest.next_token() {
+            Some((c, next)) if c == expected => rest = next,
+            _ => return None,
+        }
+    }
+    Some(rest)
+}
+
+
+pub fn string<S: AsRef<str>, I>(literal: S) -> Literal<S, I> {
+    Literal { literal, input: PhantomData }
+}
+
+
+/// The longest run of characters for which `pred` holds, returned as a
+/// slice of the input.
+///
+/// Fails if the run is shorter than `min`.
+pub struct TakeWhile<F, I> {
+    pred: F,
+    min: usize,
+    input: PhantomData<I>
+}
+
+
+impl<F: Clone, I> Clone for TakeWhile<F, I> {
+    fn clone(&self) -> Self {
+        Self {
+            pred: self.pred.clone(),
+            min: self.min,
+            input: PhantomData
+        }
+    }
+}
+
+
+impl<F: Copy, I> Copy for TakeWhile<F, I> {}
+
+
+impl<F, I> Parser<I> for TakeWhile<F, I>
+where
+    I: Input<Token = char>,
+    F: Fn(char) -> bool
+{
+    type Out = <I as Input>::Slice;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let mut rest = input.clone();
+        let mut count = 0;
+        while let Some((c, next)) = rest.next_token() {
+            if !(self.pred)(c) {
+                break;
+            }
+            rest = next;
+            count += 1;
+        }
+
+        if count < self.min {
+            return Err(ParseError::from_unexpected(&rest, None));
+        }
+        Ok((input.slice_to(&rest), rest))
+    }
+}
+
+
+/// Zero or more characters for which `pred` holds.
+pub fn take_while<F: Fn(char) -> bool, I>(pred: F) -> TakeWhile<F, I> {
+    TakeWhile { pred, min: 0, input: PhantomData }
+}
+
+
+/// One or more characters for which `pred` holds.
+pub fn take_while1<F: Fn(char) -> bool, I>(pred: F) -> TakeWhile<F, I> {
+    TakeWhile { pred, min: 1, input: PhantomData }
+}
+
+
+/// Everything up to, but not including, the first occurrence of
+/// `terminator`.
+///
+/// Fails at the end of input if `terminator` never occurs.
+pub struct TakeUntil<S, I> {
+    terminator: S,
+    input: PhantomData<I>
+}
+
+
+impl<S: Clone, I> Clone for TakeUntil<S, I> {
+    fn clone(&self) -> Self {
+        Self {
+            terminator: self.terminator.clone(),
+            input: PhantomData
+        }
+    }
+}
+
+
+impl<S: Copy, I> Copy for TakeUntil<S, I> {}
+
+
+impl<S, I> Parser<I> for TakeUntil<S, I>
+where
+    I: Input<Token = char>,
+    S: AsRef<str>
+{
+    type Out = <I as Input>::Slice;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let terminator = self.terminator.as_ref();
+        let mut rest = input.clone();
+        while strip_literal(&rest, terminator).is_none() {
+            rest = match rest.next_token() {
+                Some((_, next)) => next,
+                None => {
+                    let expected = iter::once_with(|| Expected::Literal(terminator.chars().collect()));
+                    return Err(ParseError::from_unexpected(&rest, expected));
+                }
+            };
+        }
+        Ok((input.slice_to(&rest), rest))
+    }
+}
+
+
+pub fn take_until<S: AsRef<str>, I>(terminator: S) -> TakeUntil<S, I> {
+    TakeUntil { terminator, input: PhantomData }
+}
+
+
+/// Succeeds only at the end of the input.
+pub struct Eof<I> {
+    input: PhantomData<I>
+}
+
+
+impl<I> Clone for Eof<I> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+
+impl<I> Copy for Eof<I> {}
+
+
+impl<I: Input> Parser<I> for Eof<I> {
+    type Out = ();
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        if input.is_empty() {
+            Ok(((), input))
+        } else {
+            Err(ParseError::from_unexpected(&input, Some(Expected::EndOfInput)))
+        }
+    }
+}
+
+
+pub fn eof<I>() -> Eof<I> {
+    Eof { input: PhantomData }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bytes, MemoTable, Packrat, Position, Recovering, Text};
+
+    #[test]
+    fn satisfy_test() {
+        let (result, rest) =
+            satisfy(|c| c.is_ascii_digit())
+                .call(Text::new("1a"))
+                .unwrap();
+
+        assert_eq!('1', result);
+
+        let err = satisfy(|c| c.is_ascii_digit()).call(rest).unwrap_err();
+
+        assert_eq!(Some('a'), err.found());
+        assert_eq!(1, err.offset());
+    }
+
+    #[test]
+    fn char_test() {
+        let (result, _) = char('a').call(Text::new("ab")).unwrap();
+        let err = char('a').call(Text::new("ba")).unwrap_err();
+
+        assert_eq!('a', result);
+        assert_eq!(&[Expected::Token('a')], err.expected());
+    }
+
+    #[test]
+    fn one_of_none_of_test() {
+        let (result, _) = one_of("+-").call(Text::new("-1")).unwrap();
+        let err = one_of("+-").call(Text::new("1")).unwrap_err();
+
+        assert_eq!('-', result);
+        assert_eq!(&[Expected::Token('+'), Expected::Token('-')], err.expected());
+
+        let (result, _) = none_of("\"").call(Text::new("a\"")).unwrap();
+        let err = none_of("\"").call(Text::new("\"a")).unwrap_err();
+
+        assert_eq!('a', result);
+        assert_eq!(Some('"'), err.found());
+    }
+
+    #[test]
+    fn string_test() {
+        let (result, rest) = string("let").call(Text::new("let x")).unwrap();
+
+        assert_eq!("let", result);
+        assert_eq!(" x", rest.as_str());
+
+        let err = string("let").call(Text::new("lex")).unwrap_err();
+
+        assert_eq!(&[Expected::Literal("let".chars().collect())], err.expected());
+        assert_eq!(0, err.offset());
+    }
+
+    #[test]
+    fn take_while_test() {
+        let (result, rest) =
+            take_while(|c| c.is_alphabetic())
+                .call(Text::new("héllo wörld"))
+                .unwrap();
+
+        assert_eq!("héllo", result);
+        assert_eq!(Position::new(6, 1, 6), rest.position());
+
+        let (result, _) = take_while(|c| c.is_alphabetic()).call(rest).unwrap();
+
+        assert_eq!("", result);
+    }
+
+    #[test]
+    fn take_while1_test() {
+        let err =
+            take_while1(|c| c.is_ascii_digit())
+                .call(Text::new("x"))
+                .unwrap_err();
+
+        assert_eq!(Some('x'), err.found());
+    }
+
+    #[test]
+    fn take_until_test() {
+        let (result, rest) =
+            take_until("*/")
+                .call(Text::new(" comment\n */ x"))
+                .unwrap();
+
+        assert_eq!(" comment\n ", result);
+        assert_eq!(Position::new(10, 2, 2), rest.position());
+
+        let err = take_until("*/").call(Text::new("abc")).unwrap_err();
+
+        assert_eq!(3, err.offset());
+        assert_eq!(None, err.found());
+    }
+
+    #[test]
+    fn take_until_wrapped_input_test() {
+        let (result, rest) =
+            string("/*")
+                .right(take_until("*/"))
+                .call(Recovering::new(Text::new("/* a * b */")))
+                .unwrap();
+
+        assert_eq!(" a * b ", result);
+        assert_eq!("*/", rest.inner().as_str());
+
+        let table = MemoTable::new();
+        let (result, _) = take_until("*/").call(Packrat::new(Text::new("*/"), &table)).unwrap();
+
+        assert_eq!("", result);
+    }
+
+    #[test]
+    fn eof_test() {
+        let (_, rest) = string("ab").call(Text::new("ab")).unwrap();
+
+        assert!(eof().call(rest).is_ok());
+        assert_eq!(&[Expected::EndOfInput], eof().call(Text::new("a")).unwrap_err().expected());
+        assert!(eof().call(Bytes::new(b"")).is_ok());
+    }
+}
@@ -0,0 +1,353 @@
+use crate::{Input, ParseError, ParseResult, Parser};
+
+
+/// Tries `left`, and `right` on the same input if `left` fails.
+///
+/// Alternation backtracks: `right` starts where `left` started, no matter
+/// how much `left` consumed before failing, unless `left` failed past a
+/// [`cut`]. If both fail, their errors are combined with
+/// [`ParseError::merge`].
+#[derive(Clone)]
+pub struct Or<A, B> {
+    pub(crate) left: A,
+    pub(crate) right: B
+}
+
+
+impl<A, B> Or<A, B> {
+    pub fn new(left: A, right: B) -> Self {
+        Self {
+            left,
+            right
+        }
+    }
+}
+
+
+impl<I: Input, A, B> Parser<I> for Or<A, B>
+where
+    A: Parser<I>,
+    B: Parser<I, Out = <A as Parser<I>>::Out>
+{
+    type Out = <A as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        self.left.call(input.clone()).or_else(|left| {
+            if left.is_fatal() {
+                return Err(left);
+            }
+            self.right.call(input).map_err(|right| {
+                if right.is_fatal() {
+                    right
+                } else {
+                    left.merge(right)
+                }
+            })
+        })
+    }
+}
+
+
+/// Tries each of a group of parsers in order and takes the first success.
+///
+/// The group can be a tuple of up to 12 parsers with the same `Out`, an
+/// array, a slice or a `Vec`. Backtracking and error merging follow the
+/// same rules as [`Or`].
+#[derive(Clone)]
+pub struct Choice<T> {
+    parsers: T
+}
+
+
+impl<T> Choice<T> {
+    pub fn new(parsers: T) -> Self {
+        Self { parsers }
+    }
+}
+
+
+pub fn choice<T>(parsers: T) -> Choice<T> {
+    Choice::new(parsers)
+}
+
+
+fn first_success<'p, I: Input, P, O>(
+    parsers: impl IntoIterator<Item = &'p P>,
+    input: I
+) -> ParseResult<I, O>
+where
+    P: Parser<I, Out = O> + 'p
+{
+    let mut error: Option<<I as Input>::Error> = None;
+    for parser in parsers {
+        match parser.call(input.clone()) {
+            Ok(ok) => return Ok(ok),
+            Err(e) if e.is_fatal() => return Err(e),
+            Err(e) => {
+                error = Some(match error {
+                    None => e,
+                    Some(prev) => prev.merge(e),
+                });
+            }
+        }
+    }
+
+    Err(error.unwrap_or_else(|| ParseError::from_unexpected(&input, None)))
+}
+
+
+impl<I: Input, P: Parser<I>> Parser<I> for Choice<&[P]> {
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        first_success(self.parsers, input)
+    }
+}
+
+
+impl<I: Input, P: Parser<I>> Parser<I> for Choice<Vec<P>> {
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        first_success(&self.parsers, input)
+    }
+}
+
+
+impl<I: Input, P: Parser<I>, const N: usize> Parser<I> for Choice<[P; N]> {
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        first_success(&self.parsers, input)
+    }
+}
+
+
+macro_rules! choice_tuple {
+    ($head:ident $($tail:ident)*) => {
+        impl<I: Input, $head, $($tail),*> Parser<I> for Choice<($head, $($tail,)*)>
+        where
+            $head: Parser<I>,
+            $($tail: Parser<I, Out = <$head as Parser<I>>::Out>,)*
+        {
+            type Out = <$head as Parser<I>>::Out;
+
+            #[allow(non_snake_case)]
+            fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+                let ($head, $($tail,)*) = &self.parsers;
+                #[allow(unused_mut)]
+                let mut error = match $head.call(input.clone()) {
+                    Ok(ok) => return Ok(ok),
+                    Err(e) if e.is_fatal() => return Err(e),
+                    Err(e) => e,
+                };
+                $(
+                    match $tail.call(input.clone()) {
+                        Ok(ok) => return Ok(ok),
+                        Err(e) if e.is_fatal() => return Err(e),
+                        Err(e) => error = error.merge(e),
+                    }
+                )*
+                Err(error)
+            }
+        }
+    };
+}
+
+
+choice_tuple!(P1);
+choice_tuple!(P1 P2);
+choice_tuple!(P1 P2 P3);
+choice_tuple!(P1 P2 P3 P4);
+choice_tuple!(P1 P2 P3 P4 P5);
+choice_tuple!(P1 P2 P3 P4 P5 P6);
+choice_tuple!(P1 P2 P3 P4 P5 P6 P7);
+choice_tuple!(P1 P2 P3 P4 P5 P6 P7 P8);
+choice_tuple!(P1 P2 P3 P4 P5 P6 P7 P8 P9);
+choice_tuple!(P1 P2 P3 P4 P5 P6 P7 P8 P9 P10);
+choice_tuple!(P1 P2 P3 P4 P5 P6 P7 P8 P9 P10 P11);
+choice_tuple!(P1 P2 P3 P4 P5 P6 P7 P8 P9 P10 P11 P12);
+
+
+/// Makes any failure of `parser` fatal, so that no enclosing alternative
+/// is tried and no enclosing repetition stops quietly.
+///
+/// Put a cut where the input is known to have committed to a branch, such
+/// as after an opening keyword or bracket, and the error will point at
+/// what went wrong inside that branch instead of at its start.
+#[derive(Clone)]
+pub struct Cut<P> {
+    parser: P
+}
+
+
+impl<I: Input, P: Parser<I>> Parser<I> for Cut<P> {
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        self.parser.call(input).map_err(ParseError::into_fatal)
+    }
+}
+
+
+pub fn cut<P>(parser: P) -> Cut<P> {
+    Cut { parser }
+}
+
+
+/// Makes a fatal failure of `parser` recoverable again, so that an
+/// enclosing alternative can backtrack over a [`cut`] inside it.
+#[derive(Clone)]
+pub struct Attempt<P> {
+    parser: P
+}
+
+
+impl<I: Input, P: Parser<I>> Parser<I> for Attempt<P> {
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        self.parser.call(input).map_err(ParseError::into_recoverable)
+    }
+}
+
+
+pub fn attempt<P>(parser: P) -> Attempt<P> {
+    Attempt { parser }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{char, cut, string, DetailedError, Expected, Item, Position, Text, Zero};
+
+    #[test]
+    fn or_test() {
+        let (result, rest) =
+            Zero::new()
+                .or(Item::new())
+                .call(Text::new("ab"))
+                .unwrap();
+
+        assert_eq!('a', result);
+        assert_eq!("b", rest.as_str());
+    }
+
+    #[test]
+    fn or_backtracks_test() {
+        let (result, _) =
+            Item::new()
+                .bind(|_| Zero::<Vec<char>, _>::new())
+                .or(Item::new().take(2))
+                .call(Text::new("ab"))
+                .unwrap();
+
+        assert_eq!(vec!['a', 'b'], result);
+    }
+
+    #[test]
+    fn or_keeps_furthest_error_test() {
+        let err =
+            Item::new()
+                .take(3)
+                .or(Zero::new())
+                .call(Text::new("ab"))
+                .unwrap_err();
+
+        assert_eq!(DetailedError::new(Position::new(2, 1, 3), vec![Expected::Any], None), err);
+    }
+
+    #[test]
+    fn choice_tuple_test() {
+        let (result, _) =
+            choice((Zero::new(), Zero::new(), Item::new()))
+                .call(Text::new("xyz"))
+                .unwrap();
+
+        assert_eq!('x', result);
+    }
+
+    #[test]
+    fn choice_slice_test() {
+        let parsers = [Item::new().take(3), Item::new().take(1)];
+
+        let (result, rest) =
+            choice(&parsers[..])
+                .call(Text::new("ab"))
+                .unwrap();
+
+        assert_eq!(vec!['a'], result);
+        assert_eq!("b", rest.as_str());
+    }
+
+    #[test]
+    fn choice_empty_test() {
+        let parsers: Vec<Item<Text>> = vec![];
+
+        let err = choice(parsers).call(Text::new("ab")).unwrap_err();
+
+        assert_eq!(Some('a'), err.found());
+    }
+
+    #[test]
+    fn cut_stops_or_test() {
+        let call =
+            string("f(")
+                .right(Item::new().left(char(')')).cut())
+                .or(string("f").right(Item::new()));
+
+        let err = call.call(Text::new("f(xy")).unwrap_err();
+
+        assert!(err.is_fatal());
+        assert_eq!(&[Expected::Token(')')], err.expected());
+        assert_eq!(Position::new(3, 1, 4), err.position());
+    }
+
+    #[test]
+    fn cut_in_right_branch_test() {
+        let err = char('a').or(cut(char('b'))).call(Text::new("c")).unwrap_err();
+        assert!(err.is_fatal());
+
+        let err =
+            char('a')
+                .or(cut(char('b')))
+                .or(char('c'))
+                .call(Text::new("c"))
+                .unwrap_err();
+
+        assert!(err.is_fatal());
+        assert_eq!(&[Expected::Token('b')], err.expected());
+
+        let err =
+            char('x')
+                .right(char('a').or(cut(char('b'))))
+                .many::<Vec<_>>()
+                .call(Text::new("xaxc"))
+                .unwrap_err();
+
+        assert_eq!(3, err.offset());
+    }
+
+    #[test]
+    fn cut_stops_repetition_test() {
+        let group = char('(').right(char('x').left(char(')')).cut());
+
+        let (result, rest) = group.clone().many::<Vec<_>>().call(Text::new("(x)y")).unwrap();
+        assert_eq!(vec!['x'], result);
+        assert_eq!("y", rest.as_str());
+
+        let err = group.many::<Vec<_>>().call(Text::new("(x)(y)")).unwrap_err();
+        assert_eq!(4, err.offset());
+    }
+
+    #[test]
+    fn attempt_test() {
+        let (result, _) =
+            choice((attempt(char('a').right(char('b').cut())), char('a')))
+                .call(Text::new("ac"))
+                .unwrap();
+
+        assert_eq!('a', result);
+    }
+}
@@ -0,0 +1,519 @@
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::input::{Input, Position, Token};
+
+
+/// Something a parser was looking for when it failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expected<T = char> {
+    /// Any single token.
+    Any,
+    /// This particular token.
+    Token(T),
+    /// This exact run of tokens.
+    Literal(Vec<T>),
+    /// The end of the input.
+    EndOfInput,
+    /// Something described by name; see [`Parser::label`](crate::Parser::label).
+    Label(&'static str),
+}
+
+
+impl<T: Token> fmt::Display for Expected<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Any => write!(f, "any {}", T::NAME),
+            Expected::Token(t) => t.fmt_token(f),
+            Expected::Literal(run) => T::fmt_run(run, f),
+            Expected::EndOfInput => write!(f, "end of input"),
+            Expected::Label(label) => f.write_str(label),
+        }
+    }
+}
+
+
+/// What kind of failure a [`DetailedError`] describes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The input did not match what the parser expected.
+    Unexpected,
+    /// A parser inside a repetition succeeded without consuming input, so
+    /// repeating it would never stop. This is a bug in the grammar rather
+    /// than in the input.
+    NoProgress,
+    /// A number in the input was too large for the type it is read into.
+    Overflow,
+    /// The input parsed but was rejected by the grammar's own check; the
+    /// reason is the error's [`source`](Error::source).
+    Custom,
+}
+
+
+/// The errors a grammar reports, so that it can choose how much detail to
+/// pay for.
+///
+/// Every parser builds its errors through this trait from what it knows
+/// at the point of failure, and the [`Input`] decides which implementation
+/// is used through its `Error` type. [`DetailedError`] keeps everything a
+/// person needs to fix the input; [`OffsetError`] keeps only where the
+/// failure happened, which costs next to nothing on a hot path.
+///
+/// Only construction, the offset and the [`cut`](crate::cut) flag must be
+/// provided. The rest default to the cheapest sensible behavior: other
+/// kinds of failure are reported like any mismatch, the error that got
+/// further wins a merge, and contexts and labels are ignored.
+pub trait ParseError<T: Token>: Sized + Clone + fmt::Debug {
+    /// The input did not match at `input`, which would have accepted
+    /// `expected`.
+    ///
+    /// `expected` is only iterated by errors that keep it, so it can be
+    /// built lazily.
+    fn from_unexpected<I, E>(input: &I, expected: E) -> Self
+    where
+        I: Input<Token = T>,
+        E: IntoIterator<Item = Expected<T>>;
+
+    /// A failure at `input` other than a plain mismatch; see [`ErrorKind`].
+    fn from_kind<I>(input: &I, _kind: ErrorKind) -> Self
+    where
+        I: Input<Token = T>
+    {
+        Self::from_unexpected(input, None)
+    }
+
+    /// The value starting at `input` was rejected because of `cause`; see
+    /// [`ErrorKind::Custom`].
+    fn from_custom<I, C>(input: &I, _cause: C) -> Self
+    where
+        I: Input<Token = T>,
+        C: Error + Send + Sync + 'static
+    {
+        Self::from_unexpected(input, None)
+    }
+
+    /// Byte offset at which the failure happened.
+    fn offset(&self) -> usize;
+
+    /// Whether the failure happened past a [`cut`](crate::cut), in which
+    /// case alternatives and repetitions must not backtrack over it.
+    fn is_fatal(&self) -> bool;
+
+    /// Marks the failure as fatal; see [`Cut`](crate::Cut).
+    fn into_fatal(self) -> Self;
+
+    /// Allows backtracking over the failure again; see
+    /// [`Attempt`](crate::Attempt).
+    fn into_recoverable(self) -> Self;
+
+    /// Combines the errors of two alternatives tried on the same input.
+    ///
+    /// The error that got further into the input should win, since that
+    /// branch is the one the input most likely meant.
+    fn merge(self, other: Self) -> Self {
+        if other.offset() > self.offset() {
+            other
+        } else {
+            self
+        }
+    }
+
+    /// Notes that the failure happened inside `context`, which encloses
+    /// any context already noted; see [`Parser::context`](crate::Parser::context).
+    fn add_context(self, _context: &'static str) -> Self {
+        self
+    }
+
+    /// Replaces what would have been accepted where the failure happened;
+    /// see [`Parser::label`](crate::Parser::label).
+    fn with_expected<E>(self, _expected: E) -> Self
+    where
+        E: IntoIterator<Item = Expected<T>>
+    {
+        self
+    }
+}
+
+
+/// An error that records nothing but where the failure happened.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OffsetError {
+    offset: usize,
+    fatal: bool,
+}
+
+
+impl OffsetError {
+    pub fn new(offset: usize) -> Self {
+        Self {
+            offset,
+            fatal: false
+        }
+    }
+
+    /// Byte offset at which the failure happened.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Whether the failure happened past a [`cut`](crate::cut).
+    pub fn is_fatal(&self) -> bool {
+        self.fatal
+    }
+}
+
+
+impl<T: Token> ParseError<T> for OffsetError {
+    fn from_unexpected<I, E>(input: &I, _expected: E) -> Self
+    where
+        I: Input<Token = T>,
+        E: IntoIterator<Item = Expected<T>>
+    {
+        Self::new(input.offset())
+    }
+
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn is_fatal(&self) -> bool {
+        self.fatal
+    }
+
+    fn into_fatal(mut self) -> Self {
+        self.fatal = true;
+        self
+    }
+
+    fn into_recoverable(mut self) -> Self {
+        self.fatal = false;
+        self
+    }
+}
+
+
+impl fmt::Display for OffsetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "parse error at offset {}", self.offset)
+    }
+}
+
+
+impl Error for OffsetError {}
+
+
+/// Why and where a parser failed, in full.
+///
+/// `T` is the token type of the input, so `found` and `expected` can name
+/// the actual characters or bytes involved. This is the error [`Text`] and
+/// [`Bytes`] report unless told otherwise.
+///
+/// [`Text`]: crate::Text
+/// [`Bytes`]: crate::Bytes
+#[derive(Clone, Debug)]
+pub struct DetailedError<T = char> {
+    kind: ErrorKind,
+    position: Position,
+    expected: Vec<Expected<T>>,
+    found: Option<T>,
+    fatal: bool,
+    context: Vec<&'static str>,
+    cause: Option<Arc<dyn Error + Send + Sync>>,
+}
+
+
+impl<T: Token> DetailedError<T> {
+    pub fn new(position: Position, expected: Vec<Expected<T>>, found: Option<T>) -> Self {
+        Self {
+            kind: ErrorKind::Unexpected,
+            position,
+            expected,
+            found,
+            fatal: false,
+            context: Vec::new(),
+            cause: None
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Where the failure happened.
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Byte offset at which the failure happened.
+    pub fn offset(&self) -> usize {
+        self.position.offset
+    }
+
+    /// Everything that would have been accepted at `position`.
+    pub fn expected(&self) -> &[Expected<T>] {
+        &self.expected
+    }
+
+    /// The token found at `position`, or `None` at the end of input.
+    pub fn found(&self) -> Option<T> {
+        self.found
+    }
+
+    /// What the parser was in the middle of when it failed, innermost
+    /// first; see [`Parser::context`](crate::Parser::context).
+    pub fn context(&self) -> &[&'static str] {
+        &self.context
+    }
+
+    /// Whether the failure happened past a [`cut`](crate::cut).
+    pub fn is_fatal(&self) -> bool {
+        self.fatal
+    }
+
+    // Writes what went wrong, without saying where.
+    pub(crate) fn fmt_message(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ErrorKind::NoProgress => return write!(f, "repeated parser made no progress"),
+            ErrorKind::Overflow => return write!(f, "number too large"),
+            ErrorKind::Custom => {
+                if let Some(cause) = &self.cause {
+                    return write!(f, "{}", cause);
+                }
+            }
+            ErrorKind::Unexpected => {}
+        }
+
+        if self.expected.is_empty() {
+            write!(f, "unexpected ")?;
+        } else {
+            self.fmt_expected(f)?;
+            write!(f, ", found ")?;
+        }
+
+        match self.found {
+            Some(t) => t.fmt_token(f),
+            None => write!(f, "end of input"),
+        }
+    }
+
+    // Writes the expected set as "expected a, b, or c", or nothing if it
+    // is empty.
+    pub(crate) fn fmt_expected(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.expected.as_slice() {
+            [] => Ok(()),
+            [only] => write!(f, "expected {}", only),
+            [first, second] => write!(f, "expected {} or {}", first, second),
+            [init @ .., last] => {
+                write!(f, "expected ")?;
+                for e in init {
+                    write!(f, "{}, ", e)?;
+                }
+                write!(f, "or {}", last)
+            }
+        }
+    }
+}
+
+
+impl<T: Token> ParseError<T> for DetailedError<T> {
+    fn from_unexpected<I, E>(input: &I, expected: E) -> Self
+    where
+        I: Input<Token = T>,
+        E: IntoIterator<Item = Expected<T>>
+    {
+        let found = input.next_token().map(|(t, _)| t);
+        Self::new(input.position(), expected.into_iter().collect(), found)
+    }
+
+    fn from_kind<I>(input: &I, kind: ErrorKind) -> Self
+    where
+        I: Input<Token = T>
+    {
+        Self {
+            kind,
+            ..Self::from_unexpected(input, None)
+        }
+    }
+
+    fn from_custom<I, C>(input: &I, cause: C) -> Self
+    where
+        I: Input<Token = T>,
+        C: Error + Send + Sync + 'static
+    {
+        Self {
+            kind: ErrorKind::Custom,
+            cause: Some(Arc::new(cause)),
+            ..Self::from_unexpected(input, None)
+        }
+    }
+
+    fn offset(&self) -> usize {
+        self.position.offset
+    }
+
+    fn is_fatal(&self) -> bool {
+        self.fatal
+    }
+
+    fn into_fatal(mut self) -> Self {
+        self.fatal = true;
+        self
+    }
+
+    fn into_recoverable(mut self) -> Self {
+        self.fatal = false;
+        self
+    }
+
+    /// When both failed at the same position, their expected sets are
+    /// joined, and only the contexts they were both in are kept.
+    fn merge(mut self, other: Self) -> Self {
+        if other.position.offset > self.position.offset {
+            return other;
+        }
+        if other.position.offset == self.position.offset {
+            for e in other.expected {
+                if !self.expected.contains(&e) {
+                    self.expected.push(e);
+                }
+            }
+
+            let shared = self
+                .context
+                .iter()
+                .rev()
+                .zip(other.context.iter().rev())
+                .take_while(|(a, b)| a == b)
+                .count();
+            self.context.drain(..self.context.len() - shared);
+        }
+        self
+    }
+
+    fn add_context(mut self, context: &'static str) -> Self {
+        self.context.push(context);
+        self
+    }
+
+    fn with_expected<E>(mut self, expected: E) -> Self
+    where
+        E: IntoIterator<Item = Expected<T>>
+    {
+        self.expected = expected.into_iter().collect();
+        self
+    }
+}
+
+
+impl<T: Token> fmt::Display for DetailedError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_message(f)?;
+        write!(f, " at {}", self.position)?;
+
+        for context in &self.context {
+            write!(f, ", {}", context)?;
+        }
+        Ok(())
+    }
+}
+
+
+// Causes are compared by how they read, since arbitrary error types
+// cannot be compared directly.
+impl<T: PartialEq> PartialEq for DetailedError<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+            && self.position == other.position
+            && self.expected == other.expected
+            && self.found == other.found
+            && self.fatal == other.fatal
+            && self.context == other.context
+            && self.cause.as_ref().map(|c| c.to_string()) == other.cause.as_ref().map(|c| c.to_string())
+    }
+}
+
+
+impl<T: Eq> Eq for DetailedError<T> {}
+
+
+impl<T: Token> Error for DetailedError<T> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause.as_ref().map(|c| c.as_ref() as &(dyn Error + 'static))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_test() {
+        let err = DetailedError::<char>::new(Position::new(3, 1, 4), vec![Expected::Any], None);
+
+        assert_eq!("expected any character, found end of input at 1:4", err.to_string());
+    }
+
+    #[test]
+    fn display_unexpected_test() {
+        let err = DetailedError::new(Position::new(5, 2, 3), vec![], Some('x'));
+
+        assert_eq!(5, err.offset());
+        assert_eq!("unexpected 'x' at 2:3", err.to_string());
+    }
+
+    #[test]
+    fn display_expected_list_test() {
+        let expected = vec![Expected::Token('a'), Expected::Literal("let".chars().collect()), Expected::EndOfInput];
+        let err = DetailedError::new(Position::default(), expected, Some('x'));
+
+        assert_eq!("expected 'a', \"let\", or end of input, found 'x' at 1:1", err.to_string());
+    }
+
+    #[test]
+    fn display_bytes_test() {
+        let expected = vec![Expected::Literal(b"PNG".to_vec())];
+        let err = DetailedError::new(Position::new(1, 1, 2), expected, Some(0x89u8));
+
+        assert_eq!("expected b\"PNG\", found 0x89 at 1:2", err.to_string());
+        assert_eq!("any byte", Expected::<u8>::Any.to_string());
+    }
+
+    #[test]
+    fn merge_furthest_wins_test() {
+        let near = DetailedError::new(Position::new(1, 1, 2), vec![Expected::Any], Some('a'));
+        let far = DetailedError::<char>::new(Position::new(4, 1, 5), vec![], Some('b'));
+
+        assert_eq!(far, near.clone().merge(far.clone()));
+        assert_eq!(far, far.clone().merge(near));
+    }
+
+    #[test]
+    fn merge_same_position_test() {
+        let left = DetailedError::<char>::new(Position::default(), vec![], Some('a'));
+        let right = DetailedError::new(Position::default(), vec![Expected::Any], Some('a'));
+
+        let merged = left.merge(right.clone()).merge(right);
+
+        assert_eq!(&[Expected::Any], merged.expected());
+    }
+
+    #[test]
+    fn merge_context_test() {
+        let import = DetailedError::<char>::new(Position::default(), vec![Expected::Label("import")], Some('x'))
+            .add_context("while parsing import")
+            .add_context("while parsing module");
+        let function = DetailedError::new(Position::default(), vec![Expected::Label("function")], Some('x'))
+            .add_context("while parsing module");
+
+        let merged = import.merge(function);
+
+        assert_eq!(&[Expected::Label("import"), Expected::Label("function")], merged.expected());
+        assert_eq!(&["while parsing module"], merged.context());
+        assert_eq!(
+            "expected import or function, found 'x' at 1:1, while parsing module",
+            merged.to_string()
+        );
+    }
+}
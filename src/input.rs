@@ -0,0 +1,459 @@
+use std::ascii;
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::error::{DetailedError, ParseError};
+
+
+/// A location in the source text.
+///
+/// `offset` counts bytes from the start of the source, `line` and
+/// `column` count from 1, with columns measured in characters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+
+impl Position {
+    pub fn new(offset: usize, line: usize, column: usize) -> Self {
+        Self {
+            offset,
+            line,
+            column
+        }
+    }
+
+    /// The position just past `c`, if `c` starts at `self`.
+    pub fn advance(self, c: char) -> Self {
+        if c == '\n' {
+            Self::new(self.offset + c.len_utf8(), self.line + 1, 1)
+        } else {
+            Self::new(self.offset + c.len_utf8(), self.line, self.column + 1)
+        }
+    }
+}
+
+
+impl Default for Position {
+    fn default() -> Self {
+        Self::new(0, 1, 1)
+    }
+}
+
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+
+/// The stretch of source between two positions, `end` exclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end.offset - self.start.offset
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+
+/// A single unit of input: a `char` for text, a `u8` for binary data.
+pub trait Token: Copy + PartialEq + fmt::Debug {
+    /// What one token is called in error messages.
+    const NAME: &'static str;
+
+    /// Writes a single token the way it would appear in a grammar.
+    fn fmt_token(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+
+    /// Writes a run of tokens the way it would appear in a grammar.
+    fn fmt_run(run: &[Self], f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
+
+
+impl Token for char {
+    const NAME: &'static str = "character";
+
+    fn fmt_token(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+
+    fn fmt_run(run: &[Self], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", run.iter().collect::<String>())
+    }
+}
+
+
+impl Token for u8 {
+    const NAME: &'static str = "byte";
+
+    fn fmt_token(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#04x}", self)
+    }
+
+    fn fmt_run(run: &[Self], f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let escaped: String = run
+            .iter()
+            .flat_map(|b| ascii::escape_default(*b))
+            .map(char::from)
+            .collect();
+        write!(f, "b\"{}\"", escaped)
+    }
+}
+
+
+/// Something a [`Parser`](crate::Parser) can read from.
+///
+/// An input is a cheap cursor into some source: reading a token hands back
+/// a new cursor and leaves the old one untouched, which is what lets
+/// combinators backtrack by simply keeping an earlier input around.
+pub trait Input: Clone {
+    type Token: Token;
+    /// A borrowed stretch of the source, such as `&str` or `&[u8]`.
+    type Slice;
+    /// What parsers report when they fail on this input; see
+    /// [`ParseError`].
+    type Error: ParseError<Self::Token>;
+
+    /// The next token and the input after it.
+    fn next_token(&self) -> Option<(Self::Token, Self)>;
+
+    /// The source from `self` up to the later input `end`.
+    fn slice_to(&self, end: &Self) -> Self::Slice;
+
+    /// Byte offset of `self` into the source.
+    fn offset(&self) -> usize;
+
+    /// Where `self` is in the source.
+    fn position(&self) -> Position;
+
+    fn is_empty(&self) -> bool {
+        self.next_token().is_none()
+    }
+}
+
+
+/// Text input that knows where in its source it is.
+///
+/// Parsers take a `Text` and hand back the `Text` for whatever they did
+/// not consume, so the position of a success or failure is always at hand
+/// without counting characters.
+///
+/// Failures are reported as `E`, a [`DetailedError`] unless the input was
+/// made with [`with_errors`](Text::with_errors).
+#[derive(Debug)]
+pub struct Text<'a, E = DetailedError<char>> {
+    source: &'a str,
+    position: Position,
+    errors: PhantomData<E>,
+}
+
+
+impl<'a, E> Clone for Text<'a, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+
+impl<'a, E> Copy for Text<'a, E> {}
+
+
+impl<'a, E> PartialEq for Text<'a, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source && self.position == other.position
+    }
+}
+
+
+impl<'a, E> Eq for Text<'a, E> {}
+
+
+impl<'a> Text<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self::with_errors(source)
+    }
+}
+
+
+impl<'a, E> Text<'a, E> {
+    /// Text input whose parsers report failures as `E`, such as
+    /// `Text::<OffsetError>::with_errors(source)`.
+    pub fn with_errors(source: &'a str) -> Self {
+        Self {
+            source,
+            position: Position::default(),
+            errors: PhantomData
+        }
+    }
+
+    /// The whole source, including what has already been consumed.
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// The unconsumed rest of the source.
+    pub fn as_str(&self) -> &'a str {
+        &self.source[self.position.offset..]
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    pub fn offset(&self) -> usize {
+        self.position.offset
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.position.offset == self.source.len()
+    }
+
+    /// The next character and the input after it.
+    pub fn next_char(&self) -> Option<(char, Self)> {
+        self.as_str().chars().next().map(|c| {
+            let rest = Self {
+                position: self.position.advance(c),
+                ..*self
+            };
+            (c, rest)
+        })
+    }
+
+    /// The input `len` bytes further on, which must land on a character
+    /// boundary.
+    pub fn advance(&self, len: usize) -> Self {
+        let position = self.as_str()[..len].chars().fold(self.position, Position::advance);
+        Self { position, ..*self }
+    }
+
+    /// The source text from `self` up to the later input `end`.
+    pub fn slice_to(&self, end: &Self) -> &'a str {
+        &self.source[self.position.offset..end.position.offset]
+    }
+
+    /// The span from `self` up to the later input `end`.
+    pub fn span_to(&self, end: &Self) -> Span {
+        Span::new(self.position, end.position)
+    }
+}
+
+
+impl<'a, E: ParseError<char>> Input for Text<'a, E> {
+    type Token = char;
+    type Slice = &'a str;
+    type Error = E;
+
+    fn next_token(&self) -> Option<(char, Self)> {
+        self.next_char()
+    }
+
+    fn slice_to(&self, end: &Self) -> &'a str {
+        Text::slice_to(self, end)
+    }
+
+    fn offset(&self) -> usize {
+        self.position.offset
+    }
+
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn is_empty(&self) -> bool {
+        Text::is_empty(self)
+    }
+}
+
+
+impl<'a> From<&'a str> for Text<'a> {
+    fn from(source: &'a str) -> Self {
+        Self::new(source)
+    }
+}
+
+
+/// Binary input, read one byte at a time.
+///
+/// Binary data has no lines, so positions report line 1 and a column one
+/// past the byte offset. Failures are reported as `E`, as with [`Text`].
+#[derive(Debug)]
+pub struct Bytes<'a, E = DetailedError<u8>> {
+    source: &'a [u8],
+    offset: usize,
+    errors: PhantomData<E>,
+}
+
+
+impl<'a, E> Clone for Bytes<'a, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+
+impl<'a, E> Copy for Bytes<'a, E> {}
+
+
+impl<'a, E> PartialEq for Bytes<'a, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source && self.offset == other.offset
+    }
+}
+
+
+impl<'a, E> Eq for Bytes<'a, E> {}
+
+
+impl<'a> Bytes<'a> {
+    pub fn new(source: &'a [u8]) -> Self {
+        Self::with_errors(source)
+    }
+}
+
+
+impl<'a, E> Bytes<'a, E> {
+    /// Binary input whose parsers report failures as `E`.
+    pub fn with_errors(source: &'a [u8]) -> Self {
+        Self {
+            source,
+            offset: 0,
+            errors: PhantomData
+        }
+    }
+
+    /// The whole source, including what has already been consumed.
+    pub fn source(&self) -> &'a [u8] {
+        self.source
+    }
+
+    /// The unconsumed rest of the source.
+    pub fn as_slice(&self) -> &'a [u8] {
+        &self.source[self.offset..]
+    }
+
+    /// The input `len` bytes further on.
+    pub fn advance(&self, len: usize) -> Self {
+        Self {
+            offset: self.offset + len,
+            ..*self
+        }
+    }
+}
+
+
+impl<'a, E: ParseError<u8>> Input for Bytes<'a, E> {
+    type Token = u8;
+    type Slice = &'a [u8];
+    type Error = E;
+
+    fn next_token(&self) -> Option<(u8, Self)> {
+        self.as_slice().first().map(|&b| (b, self.advance(1)))
+    }
+
+    fn slice_to(&self, end: &Self) -> &'a [u8] {
+        &self.source[self.offset..end.offset]
+    }
+
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn position(&self) -> Position {
+        Position::new(self.offset, 1, self.offset + 1)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.offset == self.source.len()
+    }
+}
+
+
+impl<'a> From<&'a [u8]> for Bytes<'a> {
+    fn from(source: &'a [u8]) -> Self {
+        Self::new(source)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_char_tracks_lines_test() {
+        let input = Text::new("a\nb");
+
+        let (_, input) = input.next_char().unwrap();
+        assert_eq!(Position::new(1, 1, 2), input.position());
+
+        let (_, input) = input.next_char().unwrap();
+        assert_eq!(Position::new(2, 2, 1), input.position());
+
+        let (c, input) = input.next_char().unwrap();
+        assert_eq!('b', c);
+        assert_eq!(Position::new(3, 2, 2), input.position());
+        assert!(input.is_empty());
+        assert!(input.next_char().is_none());
+    }
+
+    #[test]
+    fn as_str_test() {
+        let (_, input) = Text::new("hello").next_char().unwrap();
+
+        assert_eq!("ello", input.as_str());
+        assert_eq!("hello", input.source());
+    }
+
+    #[test]
+    fn advance_test() {
+        let start = Text::new("ab\ncd");
+        let end = start.advance(4);
+
+        assert_eq!(Position::new(4, 2, 2), end.position());
+        assert_eq!("ab\nc", start.slice_to(&end));
+    }
+
+    #[test]
+    fn text_multibyte_test() {
+        let input = Text::new("é🦀");
+
+        let (c, input) = input.next_token().unwrap();
+        assert_eq!('é', c);
+        assert_eq!(Position::new(2, 1, 2), Input::position(&input));
+
+        let (c, input) = input.next_token().unwrap();
+        assert_eq!('🦀', c);
+        assert_eq!(6, Input::offset(&input));
+        assert!(Input::is_empty(&input));
+    }
+
+    #[test]
+    fn bytes_test() {
+        let start = Bytes::new(&[0xff, 0x00, 0x7f]);
+
+        let (b, input) = start.next_token().unwrap();
+        assert_eq!(0xff, b);
+        assert_eq!(Position::new(1, 1, 2), input.position());
+        assert_eq!(&[0x00, 0x7f], input.as_slice());
+        assert_eq!(&[0xff], start.slice_to(&input));
+        assert!(!input.is_empty());
+        assert!(input.advance(2).is_empty());
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{Expected, Input, ParseError, ParseResult, Parser};
+
+
+/// Describes what `parser` matches by name in its errors.
+///
+/// If `parser` fails without getting past the point it started at, its
+/// expected set is replaced by `label`, so the error reads "expected
+/// function signature" rather than listing every token a signature could
+/// start with. A failure further in is left as it is, since the details
+/// are then more useful than the name.
+#[derive(Clone)]
+pub struct Label<P> {
+    parser: P,
+    label: &'static str
+}
+
+
+impl<P> Label<P> {
+    pub fn new(parser: P, label: &'static str) -> Self {
+        Self { parser, label }
+    }
+}
+
+
+impl<I: Input, P: Parser<I>> Parser<I> for Label<P> {
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let start = input.offset();
+        self.parser.call(input).map_err(|e| {
+            if e.offset() == start {
+                e.with_expected(Some(Expected::Label(self.label)))
+            } else {
+                e
+            }
+        })
+    }
+}
+
+
+/// Notes on every error from `parser` that it happened inside `context`,
+/// such as "while parsing import".
+///
+/// Nested contexts stack up, so an error can tell everything it was in the
+/// middle of; see [`DetailedError::context`](crate::DetailedError::context).
+#[derive(Clone)]
+pub struct Context<P> {
+    parser: P,
+    context: &'static str
+}
+
+
+impl<P> Context<P> {
+    pub fn new(parser: P, context: &'static str) -> Self {
+        Self { parser, context }
+    }
+}
+
+
+impl<I: Input, P: Parser<I>> Parser<I> for Context<P> {
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        self.parser.call(input).map_err(|e| e.add_context(self.context))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{char, choice, satisfy, string, take_while1, Text};
+
+    fn signature<'a>() -> impl Parser<Text<'a>, Out = &'a str> {
+        string("fn ")
+            .right(take_while1(|c| c.is_alphabetic()))
+            .left(char('('))
+            .left(char(')'))
+            .label("function signature")
+    }
+
+    #[test]
+    fn label_test() {
+        let err = satisfy(|c| c.is_ascii_lowercase()).label("identifier").call(Text::new("1")).unwrap_err();
+
+        assert_eq!(&[Expected::Label("identifier")], err.expected());
+        assert_eq!("expected identifier, found '1' at 1:1", err.to_string());
+    }
+
+    #[test]
+    fn label_after_progress_test() {
+        let err = signature().call(Text::new("fn main(;")).unwrap_err();
+
+        assert_eq!(8, err.offset());
+        assert_eq!(&[Expected::Token(')')], err.expected());
+    }
+
+    #[test]
+    fn context_test() {
+        let import = string("import ").right(take_while1(|c| c.is_alphabetic())).left(char(';'));
+
+        let err = import.context("while parsing import").call(Text::new("import b,")).unwrap_err();
+
+        assert_eq!(8, err.offset());
+        assert_eq!(&[Expected::Token(';')], err.expected());
+        assert_eq!(&["while parsing import"], err.context());
+    }
+
+    #[test]
+    fn nested_context_test() {
+        let err =
+            signature()
+                .context("while parsing function")
+                .context("while parsing module")
+                .call(Text::new("fn main(;"))
+                .unwrap_err();
+
+        assert_eq!(&["while parsing function", "while parsing module"], err.context());
+        assert_eq!(
+            "expected ')', found ';' at 1:9, while parsing function, while parsing module",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn merge_labels_test() {
+        let item = choice((
+            signature().context("while parsing function"),
+            string("use ").label("import").context("while parsing import"),
+        ));
+
+        let err = item.context("while parsing item").call(Text::new("struct")).unwrap_err();
+
+        assert_eq!(&[Expected::Label("function signature"), Expected::Label("import")], err.expected());
+        assert_eq!(&["while parsing item"], err.context());
+    }
+}
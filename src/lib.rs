@@ -1,53 +1,264 @@
 // use std::convert::TryInto;
 // use std::array::TryFromSliceError;
-use std::rc::Rc;
-
-pub trait Parser {
+use std::error::Error;
+use std::iter::FromIterator;
+
+mod binary;
+mod boxed;
+mod chain;
+mod character;
+mod choice;
+mod error;
+mod input;
+mod label;
+mod lookahead;
+mod memo;
+mod nondet;
+mod pratt;
+mod recovery;
+mod recursive;
+mod repeat;
+mod report;
+mod sequence;
+
+pub use crate::binary::{
+    align, be_i16, be_i32, be_i64, be_u16, be_u32, be_u64, le_i16, le_i32, le_i64, le_u16, le_u32,
+    le_u64, length_prefixed, sleb128, tag, take_bytes, uleb128, Align, Endian, FixedWidth, Int,
+    LengthPrefixed, Sleb128, Tag, TakeBytes, Uleb128,
+};
+pub use crate::boxed::{BoxedParser, SyncBoxedParser};
+pub use crate::chain::{chainl1, chainr1, postfix, prefix, ChainL1, ChainR1, Postfix, Prefix};
+pub use crate::character::{
+    char, eof, none_of, one_of, satisfy, string, take_until, take_while, take_while1, Char, Eof,
+    Literal, NoneOf, OneOf, Satisfy, TakeUntil, TakeWhile,
+};
+pub use crate::choice::{attempt, choice, cut, Attempt, Choice, Cut, Or};
+pub use crate::error::{DetailedError, ErrorKind, Expected, OffsetError, ParseError};
+pub use crate::input::{Bytes, Input, Position, Span, Text, Token};
+pub use crate::label::{Context, Label};
+pub use crate::lookahead::{not, peek, Not, Peek};
+pub use crate::memo::{Memo, MemoStats, MemoTable, Packrat};
+pub use crate::nondet::{once, NondetParser, Once};
+pub use crate::pratt::{pratt, pratt_sync, Assoc, Pratt, PrattParts};
+pub use crate::recovery::{
+    delimited_recovery, skip_until, DelimitedRecovery, RecoverWith, Recovering, SkipUntil,
+};
+pub use crate::recursive::{recursive, recursive_sync, Recursive};
+pub use crate::repeat::{
+    at_least, at_most, end_by, repeat, repeat_fold, sep_by, sep_by1, sep_end_by, Many, Repeat,
+    RepeatFold, SepBy,
+};
+pub use crate::report::Report;
+pub use crate::sequence::{between, pair, Between, Left, Right, Then};
+
+/// The output of a parser together with the unconsumed rest of the input.
+pub type ParseResult<I, O> = Result<(O, I), <I as Input>::Error>;
+
+pub trait Parser<I: Input> {
     type Out;
 
-    fn call<'a>(&self, input: &'a str) -> Option<(Self::Out, &'a str)>;
+    fn call(&self, input: I) -> ParseResult<I, Self::Out>;
 
 
     fn map<F, A>(self, func: F) -> Map<Self, F>
     where
         Self: Sized,
-        F: Fn(<Self as Parser>::Out) -> A
+        F: Fn(<Self as Parser<I>>::Out) -> A
     {
         Map::new(self, func)
     }
 
+    /// Like [`map`](Parser::map), but `func` may reject the output; see
+    /// [`TryMap`].
+    fn try_map<F, A, E>(self, func: F) -> TryMap<Self, F>
+    where
+        Self: Sized,
+        F: Fn(<Self as Parser<I>>::Out) -> Result<A, E>,
+        E: Error + Send + Sync + 'static
+    {
+        TryMap::new(self, func)
+    }
+
+    /// Fails where `pred` does not hold for the output; see [`Verify`].
+    fn verify<F>(self, pred: F) -> Verify<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&<Self as Parser<I>>::Out) -> bool
+    {
+        Verify::new(self, pred)
+    }
+
     fn bind<F, Q>(self, func: F) -> Bind<Self, F>
     where
         Self: Sized,
-        Q: Parser,
-        F: Fn(<Self as Parser>::Out) -> Q 
+        Q: Parser<I>,
+        F: Fn(<Self as Parser<I>>::Out) -> Q 
     {
         Bind::new(self, func)
     }
 
-    fn take(self, n: i32) -> Take<Self>
+    /// Exactly `n` repetitions of `self`; see [`repeat`] for ranges.
+    fn take(self, n: usize) -> Take<Self>
     where
         Self: Sized,
     {
         Take::new(n, self)
     }
+
+    /// Zero or more repetitions of `self`, collected into `C`.
+    fn many<C>(self) -> Many<Self, C>
+    where
+        Self: Sized,
+        C: FromIterator<<Self as Parser<I>>::Out>
+    {
+        Many::new(self, 0)
+    }
+
+    /// One or more repetitions of `self`, collected into `C`.
+    fn many1<C>(self) -> Many<Self, C>
+    where
+        Self: Sized,
+        C: FromIterator<<Self as Parser<I>>::Out>
+    {
+        Many::new(self, 1)
+    }
+
+    /// Falls back to `other` when `self` fails; see [`Or`] for the
+    /// backtracking rules.
+    fn or<Q>(self, other: Q) -> Or<Self, Q>
+    where
+        Self: Sized,
+        Q: Parser<I, Out = <Self as Parser<I>>::Out>
+    {
+        Or::new(self, other)
+    }
+
+    /// Makes failures of `self` fatal; see [`Cut`].
+    fn cut(self) -> Cut<Self>
+    where
+        Self: Sized,
+    {
+        cut(self)
+    }
+
+    /// Runs `other` after `self` and keeps both outputs as a pair.
+    fn then<Q>(self, other: Q) -> Then<Self, Q>
+    where
+        Self: Sized,
+        Q: Parser<I>
+    {
+        Then::new(self, other)
+    }
+
+    /// Runs `other` after `self` and keeps only the output of `self`.
+    fn left<Q>(self, other: Q) -> Left<Self, Q>
+    where
+        Self: Sized,
+        Q: Parser<I>
+    {
+        Left::new(self, other)
+    }
+
+    /// Runs `other` after `self` and keeps only the output of `other`.
+    fn right<Q>(self, other: Q) -> Right<Self, Q>
+    where
+        Self: Sized,
+        Q: Parser<I>
+    {
+        Right::new(self, other)
+    }
+
+    /// Requires `other` to match after `self`, without consuming it.
+    fn followed_by<Q>(self, other: Q) -> Left<Self, Peek<Q>>
+    where
+        Self: Sized,
+        Q: Parser<I>
+    {
+        Left::new(self, peek(other))
+    }
+
+    /// Requires `other` not to match after `self`, e.g. to keep a keyword
+    /// from matching the start of a longer identifier.
+    fn not_followed_by<Q>(self, other: Q) -> Left<Self, Not<Q>>
+    where
+        Self: Sized,
+        Q: Parser<I>
+    {
+        Left::new(self, not(other))
+    }
+
+    /// Names what `self` matches in its errors; see [`Label`].
+    fn label(self, label: &'static str) -> Label<Self>
+    where
+        Self: Sized,
+    {
+        Label::new(self, label)
+    }
+
+    /// Notes on errors from `self` what it was parsing; see [`Context`].
+    fn context(self, context: &'static str) -> Context<Self>
+    where
+        Self: Sized,
+    {
+        Context::new(self, context)
+    }
+
+    /// Pairs the output with the span of input it was parsed from.
+    fn spanned(self) -> Spanned<Self>
+    where
+        Self: Sized,
+    {
+        Spanned::new(self)
+    }
+
+    /// Recovers from failures of `self` by skipping input with `strategy`;
+    /// see [`RecoverWith`].
+    fn recover_with<R>(self, strategy: R) -> RecoverWith<Self, R>
+    where
+        Self: Sized,
+    {
+        RecoverWith::new(self, strategy)
+    }
+
+    /// Remembers the results of `self` for the rest of a parse; see
+    /// [`MemoTable`].
+    fn memo(self) -> Memo<Self>
+    where
+        Self: Sized,
+    {
+        Memo::new(self)
+    }
+
+    /// Erases the type of `self`; see [`BoxedParser`].
+    fn boxed<'a>(self) -> BoxedParser<'a, I, <Self as Parser<I>>::Out>
+    where
+        Self: Sized + 'a
+    {
+        BoxedParser::new(self)
+    }
+
+    /// Erases the type of `self`, keeping it shareable between threads;
+    /// see [`SyncBoxedParser`].
+    fn boxed_sync<'a>(self) -> SyncBoxedParser<'a, I, <Self as Parser<I>>::Out>
+    where
+        Self: Sized + Send + Sync + 'a
+    {
+        SyncBoxedParser::new(self)
+    }
 }
 
 // https://doc.rust-lang.org/src/core/iter/traits/iterator.rs.html#97-3286
 // https://doc.rust-lang.org/src/core/iter/adapters/mod.rs.html#884-887
 
 
+#[derive(Clone)]
 pub struct Map<P, F> {
     parser: P,
     func: F 
 }
 
 
-impl<P, F, A> Map<P, F>
-where
-    P: Parser,
-    F: Fn(<P as Parser>::Out) -> A 
-{
+impl<P, F> Map<P, F> {
     pub fn new(parser: P, func: F) -> Self {
         Self {
             parser,
@@ -57,14 +268,14 @@ where
 }
 
 
-impl<P, F, A> Parser for Map<P, F>  
+impl<I: Input, P, F, A> Parser<I> for Map<P, F>  
 where
-    P: Parser,
-    F: Fn(<P as Parser>::Out) -> A
+    P: Parser<I>,
+    F: Fn(<P as Parser<I>>::Out) -> A
 {
     type Out = A;
 
-    fn call<'a>(&self, input: &'a str) -> Option<(Self::Out, &'a str)> {
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
         self.parser.call(input).map(move |(a, b)| {
             ((self.func)(a), b)
         })
@@ -72,18 +283,96 @@ where
 }
 
 
-pub struct Bind<P, F> {
+/// Converts the output of `parser` with `func`, which may fail.
+///
+/// An `Err` from `func` becomes a failure at the position `parser` started
+/// at, built with [`ParseError::from_custom`]. A [`DetailedError`] is then
+/// of kind [`ErrorKind::Custom`] with the `Err` as its
+/// [`source`](Error::source), so the caller can get its own error type
+/// back by downcasting.
+#[derive(Clone)]
+pub struct TryMap<P, F> {
     parser: P,
     func: F
 }
 
 
-impl<P, F, Q> Bind<P, F> 
+impl<P, F> TryMap<P, F> {
+    pub fn new(parser: P, func: F) -> Self {
+        Self {
+            parser,
+            func
+        }
+    }
+}
+
+
+impl<I: Input, P, F, A, E> Parser<I> for TryMap<P, F>
 where
-    P: Parser,
-    Q: Parser,
-    F: Fn(<P as Parser>::Out) -> Q,
+    P: Parser<I>,
+    F: Fn(<P as Parser<I>>::Out) -> Result<A, E>,
+    E: Error + Send + Sync + 'static
 {
+    type Out = A;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let (a, rest) = self.parser.call(input.clone())?;
+        match (self.func)(a) {
+            Ok(b) => Ok((b, rest)),
+            Err(e) => Err(ParseError::from_custom(&input, e)),
+        }
+    }
+}
+
+
+/// Rejects outputs of `parser` for which `pred` does not hold, failing at
+/// the position `parser` started at.
+///
+/// The error expects nothing in particular, so a [`label`](Parser::label)
+/// can say what was wanted instead.
+#[derive(Clone)]
+pub struct Verify<P, F> {
+    parser: P,
+    pred: F
+}
+
+
+impl<P, F> Verify<P, F> {
+    pub fn new(parser: P, pred: F) -> Self {
+        Self {
+            parser,
+            pred
+        }
+    }
+}
+
+
+impl<I: Input, P, F> Parser<I> for Verify<P, F>
+where
+    P: Parser<I>,
+    F: Fn(&<P as Parser<I>>::Out) -> bool
+{
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let (a, rest) = self.parser.call(input.clone())?;
+        if (self.pred)(&a) {
+            Ok((a, rest))
+        } else {
+            Err(ParseError::from_unexpected(&input, None))
+        }
+    }
+}
+
+
+#[derive(Clone)]
+pub struct Bind<P, F> {
+    parser: P,
+    func: F
+}
+
+
+impl<P, F> Bind<P, F> {
     pub fn new(parser: P, func: F) -> Self {
         Self {
             parser,
@@ -93,15 +382,15 @@ where
 }
 
 
-impl<P, F, Q> Parser for Bind<P, F> 
+impl<I: Input, P, F, Q> Parser<I> for Bind<P, F> 
 where
-    P: Parser,
-    Q: Parser,
-    F: Fn(<P as Parser>::Out) -> Q 
+    P: Parser<I>,
+    Q: Parser<I>,
+    F: Fn(<P as Parser<I>>::Out) -> Q 
 {
-    type Out = <Q as Parser>::Out;
+    type Out = <Q as Parser<I>>::Out;
 
-    fn call<'a>(&self, input: &'a str) -> Option<(Self::Out, &'a str)> {
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
         self.parser.call(input).and_then(|(a, b)| {
             (self.func)(a).call(b)
         })
@@ -132,101 +421,178 @@ where
 
 // impl<P: Parser> ParserOps for P {}
 
-pub struct Zero<A> {
-    phantom: std::marker::PhantomData<A>
+pub struct Zero<A, I> {
+    phantom: std::marker::PhantomData<(A, I)>
+}
+
+
+impl<A, I> Clone for Zero<A, I> {
+    fn clone(&self) -> Self {
+        *self
+    }
 }
 
 
-impl<A> Zero<A> {
+impl<A, I> Copy for Zero<A, I> {}
+
+
+impl<A, I> Zero<A, I> {
     pub fn new() -> Self { 
         Self { phantom: std::marker::PhantomData } 
     }
 }
 
 
-impl<A> Parser for Zero<A> {
+impl<A, I> Default for Zero<A, I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+impl<A, I: Input> Parser<I> for Zero<A, I> {
     type Out = A;
 
-    fn call<'a>(&self, _input: &'a str) -> Option<(Self::Out, &'a str)> {
-        None
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        Err(ParseError::from_unexpected(&input, None))
     }
 }
 
 
-pub struct Return<A> {
-    data: Rc<A>
+/// Succeeds with a clone of `data` without consuming any input.
+pub struct Return<A, I> {
+    data: A,
+    phantom: std::marker::PhantomData<I>
 }
 
 
-impl<A> Return<A> {
+impl<A: Clone, I> Clone for Return<A, I> {
+    fn clone(&self) -> Self {
+        Self::new(self.data.clone())
+    }
+}
+
+
+impl<A, I> Return<A, I> {
     pub fn new(data: A) -> Self {
-        Self { data: Rc::new(data) }
+        Self { data, phantom: std::marker::PhantomData }
+    }
+}
+
+
+impl<A: Clone, I: Input> Parser<I> for Return<A, I> {
+    type Out = A;
+
+    fn call(&self, input: I) -> ParseResult<I, A> {
+        Ok((self.data.clone(), input))
     }
 }
 
 
-impl<A> Parser for Return<A> {
-    type Out = Rc<A>;
+pub fn pure<A, I>(data: A) -> Return<A, I> {
+    Return::new(data)
+}
+
+
+/// Any single token of the input.
+pub struct Item<I> {
+    phantom: std::marker::PhantomData<I>
+}
 
-    fn call<'b>(&self, input: &'b str) -> Option<(Rc<A>, &'b str)> {
-        Some((Rc::clone(&self.data), input))
+
+impl<I> Clone for Item<I> {
+    fn clone(&self) -> Self {
+        *self
     }
 }
 
 
-pub struct Item {}
+impl<I> Copy for Item<I> {}
 
 
-impl Item {
+impl<I> Item<I> {
     pub fn new() -> Self {
-        Self {}
+        Self { phantom: std::marker::PhantomData }
     }
 }
 
 
-impl Parser for Item {
-    type Out = char;
+impl<I> Default for Item<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 
-    fn call<'a>(&self, input: &'a str) -> Option<(Self::Out, &'a str)> {
+impl<I: Input> Parser<I> for Item<I> {
+    type Out = <I as Input>::Token;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
         input
-            .chars()
-            .next()
-            .map(|c| (c, &input[1..]))
+            .next_token()
+            .ok_or_else(|| ParseError::from_unexpected(&input, Some(Expected::Any)))
     }
 }
 
 
+#[derive(Clone)]
 pub struct Take<P> {
-    count: i32,
+    count: usize,
     parser: P
 }
 
 
-impl<P: Parser> Take<P> {
-    pub fn new(count: i32, parser: P) -> Self {
+impl<P> Take<P> {
+    pub fn new(count: usize, parser: P) -> Self {
         Self { count, parser }
     }
 }
 
 
-impl<P: Parser> Parser for Take<P> {
-    type Out = Vec<<P as Parser>::Out>;
+impl<I: Input, P: Parser<I>> Parser<I> for Take<P> {
+    type Out = Vec<<P as Parser<I>>::Out>;
 
-    fn call<'a>(&self, input: &'a str) -> Option<(Self::Out, &'a str)> {
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
         
         let mut v = Vec::new();
         let mut rest = input;
         for _ in 0..self.count {
             match self.parser.call(rest) {
-                None => return None,
-                Some((item, string)) => {
+                Err(e) => return Err(e),
+                Ok((item, string)) => {
                     v.push(item);
                     rest = string;
                 }
             }
         }
 
-        Some((v, rest))
+        Ok((v, rest))
+    }
+}
+
+
+#[derive(Clone)]
+pub struct Spanned<P> {
+    parser: P
+}
+
+
+impl<P> Spanned<P> {
+    pub fn new(parser: P) -> Self {
+        Self { parser }
+    }
+}
+
+
+impl<I: Input, P: Parser<I>> Parser<I> for Spanned<P> {
+    type Out = (<P as Parser<I>>::Out, Span);
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let start = input.position();
+        self.parser.call(input).map(|(a, rest)| {
+            let span = Span::new(start, rest.position());
+            ((a, span), rest)
+        })
     }
 }
 
@@ -242,23 +608,58 @@ mod tests {
         let (result, _) = 
             Item::new()
                 .map(|x| format!("hi, {}", x))
-                .call(&stuff)
+                .call(Text::new(stuff))
                 .unwrap();
 
         assert_eq!("hi, h", result);
     }
 
+    #[test]
+    fn try_map_test() {
+        let number = || take_while1(|c| c.is_ascii_digit()).try_map(|digits: &str| digits.parse::<u8>());
+
+        let (result, rest) = number().call(Text::new("255,")).unwrap();
+        assert_eq!(255, result);
+        assert_eq!(",", rest.as_str());
+
+        let err =
+            char(',')
+                .right(number())
+                .call(Text::new(",256"))
+                .unwrap_err();
+
+        assert_eq!(ErrorKind::Custom, err.kind());
+        assert_eq!(1, err.offset());
+        assert_eq!(Some('2'), err.found());
+        assert_eq!("number too large to fit in target type at 1:2", err.to_string());
+
+        let cause = err.source().and_then(|e| e.downcast_ref::<std::num::ParseIntError>());
+        assert_eq!(Some(&std::num::IntErrorKind::PosOverflow), cause.map(|e| e.kind()));
+    }
+
+    #[test]
+    fn verify_test() {
+        let keyword = take_while1(|c| c.is_alphabetic()).verify(|word| ["if", "let"].contains(word));
+
+        let (result, _) = keyword.clone().call(Text::new("let x")).unwrap();
+        assert_eq!("let", result);
+
+        let err = keyword.label("keyword").call(Text::new("lex x")).unwrap_err();
+        assert_eq!(0, err.offset());
+        assert_eq!("expected keyword, found 'l' at 1:1", err.to_string());
+    }
+
     #[test]
     fn bind_test() {
         let stuff = "hello world";
 
         let (result, _) = 
             Item::new()
-                .bind(|x| Return::new(x.to_uppercase().to_string()))
-                .call(&stuff)
+                .bind(|x: char| Return::new(x.to_uppercase().to_string()))
+                .call(Text::new(stuff))
                 .unwrap();
 
-        assert_eq!("H", *result);
+        assert_eq!("H", result);
     }
 
     #[test]
@@ -267,11 +668,11 @@ mod tests {
 
         let (result, rest) = 
             Take::new(3, Item::new())
-                .call(&stuff)
+                .call(Text::new(stuff))
                 .unwrap();
 
         assert_eq!(vec!['h', 'e', 'l'], result);
-        assert_eq!("lo world", rest);
+        assert_eq!("lo world", rest.as_str());
     }
 
 
@@ -282,11 +683,11 @@ mod tests {
         let (result, rest) = 
             Take::new(3, Item::new())
                 .map(|it| it.len()) 
-                .call(&stuff)
+                .call(Text::new(stuff))
                 .unwrap();
 
         assert_eq!(3, result);
-        assert_eq!("lo world", rest);
+        assert_eq!("lo world", rest.as_str());
     }
 
     #[test]
@@ -296,11 +697,11 @@ mod tests {
         let (result, rest) = 
             Take::new(3, Item::new())
                 .map(|it| it.iter().map(|c| format!("{}!", c)).collect::<Vec<String>>()) 
-                .call(&stuff)
+                .call(Text::new(stuff))
                 .unwrap();
 
         assert_eq!(vec!["h!".to_string(), "e!".to_string(), "l!".to_string()], result);
-        assert_eq!("lo world", rest);
+        assert_eq!("lo world", rest.as_str());
     }
 
     #[test]
@@ -311,10 +712,218 @@ mod tests {
             Item::new()
                 .take(3)
                 .map(|it| it.iter().map(|c| format!("{}!", c)).collect::<Vec<String>>()) 
-                .call(&stuff)
+                .call(Text::new(stuff))
                 .unwrap();
 
         assert_eq!(vec!["h!".to_string(), "e!".to_string(), "l!".to_string()], result);
-        assert_eq!("lo world", rest);
+        assert_eq!("lo world", rest.as_str());
+    }
+
+    #[test]
+    fn item_error_test() {
+        let err = Item::new().call(Text::new("")).unwrap_err();
+
+        assert_eq!(DetailedError::new(Position::default(), vec![Expected::Any], None), err);
+    }
+
+    #[test]
+    fn zero_error_test() {
+        let err = Zero::<char, _>::new().call(Text::new("hi")).unwrap_err();
+
+        assert_eq!(0, err.offset());
+        assert_eq!(Some('h'), err.found());
+    }
+
+    #[test]
+    fn take_error_position_test() {
+        let err = Item::new().take(5).call(Text::new("a\nb")).unwrap_err();
+
+        assert_eq!(Position::new(3, 2, 2), err.position());
+        assert_eq!(&[Expected::Any], err.expected());
+    }
+
+    #[test]
+    fn bind_error_position_test() {
+        let err =
+            Item::new()
+                .bind(|_| Item::new().take(2))
+                .call(Text::new("ab"))
+                .unwrap_err();
+
+        assert_eq!(2, err.offset());
+    }
+
+    #[test]
+    fn item_multibyte_test() {
+        let (result, rest) = Item::new().call(Text::new("éa")).unwrap();
+
+        assert_eq!('é', result);
+        assert_eq!(Position::new(2, 1, 2), rest.position());
+    }
+
+    #[test]
+    fn item_bytes_test() {
+        let (result, rest) =
+            Item::new()
+                .take(2)
+                .call(Bytes::new(&[0xca, 0xfe, 0x00]))
+                .unwrap();
+
+        assert_eq!(vec![0xca, 0xfe], result);
+        assert_eq!(&[0x00], rest.as_slice());
+    }
+
+    #[test]
+    fn clone_test() {
+        let digit = Item::new().map(|c: char| c.to_digit(10).unwrap());
+        let digits = digit.clone().then(digit.take(2));
+
+        let (result, _) = digits.clone().call(Text::new("123")).unwrap();
+
+        assert_eq!((1, vec![2, 3]), result);
+        assert!(digits.call(Text::new("12")).is_err());
+    }
+
+    #[test]
+    fn copy_test() {
+        let item = Item::new();
+
+        let (result, _) = item.then(item).call(Text::new("ab")).unwrap();
+
+        assert_eq!(('a', 'b'), result);
+        assert!(item.call(Text::new("c")).is_ok());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn by_reference_test() {
+        let space = one_of(String::from(" \t")).many::<String>();
+        let word = take_while1(|c| c.is_alphabetic());
+
+        let (result, _) =
+            (&space)
+                .right(word.left(&space))
+                .many::<Vec<_>>()
+                .call(Text::new(" let \t x  "))
+                .unwrap();
+
+        assert_eq!(vec!["let", "x"], result);
+        assert!(space.call(Text::new("")).is_ok());
+    }
+
+    #[test]
+    fn pure_test() {
+        let (result, rest) =
+            pure(vec![1, 2])
+                .then(Item::new())
+                .call(Text::new("ab"))
+                .unwrap();
+
+        assert_eq!((vec![1, 2], 'a'), result);
+        assert_eq!("b", rest.as_str());
+    }
+
+    #[test]
+    fn share_between_threads_test() {
+        let digits = take_while1(|c: char| c.is_ascii_digit()).map(|d: &str| d.parse::<u32>().unwrap());
+        let number = recursive_sync(|number| choice((digits, between(char('('), number, char(')')))));
+        let grammar = std::sync::Arc::new(number.then(pure(0)).boxed_sync());
+
+        let workers: Vec<_> = ["7", "(42)", "((9))"]
+            .iter()
+            .map(|source| {
+                let grammar = std::sync::Arc::clone(&grammar);
+                std::thread::spawn(move || grammar.call(Text::new(source)).unwrap().0)
+            })
+            .collect();
+
+        let results: Vec<_> = workers.into_iter().map(|w| w.join().unwrap()).collect();
+        assert_eq!(vec![(7, 0), (42, 0), (9, 0)], results);
+    }
+
+    #[test]
+    fn spanned_test() {
+        let (_, rest) = Item::new().take(2).call(Text::new("a\nbcd")).unwrap();
+
+        let ((result, span), _) =
+            Item::new()
+                .take(2)
+                .spanned()
+                .call(rest)
+                .unwrap();
+
+        assert_eq!(vec!['b', 'c'], result);
+        assert_eq!(Span::new(Position::new(2, 2, 1), Position::new(4, 2, 3)), span);
+    }
+
+    // Keeps only the contexts a failure happened in.
+    #[derive(Clone, Debug, PartialEq)]
+    struct Contexts {
+        offset: usize,
+        fatal: bool,
+        context: Vec<&'static str>,
+    }
+
+    impl ParseError<char> for Contexts {
+        fn from_unexpected<I, E>(input: &I, _: E) -> Self
+        where
+            I: Input<Token = char>,
+            E: IntoIterator<Item = Expected<char>>
+        {
+            Self { offset: input.offset(), fatal: false, context: vec![] }
+        }
+
+        fn offset(&self) -> usize {
+            self.offset
+        }
+
+        fn is_fatal(&self) -> bool {
+            self.fatal
+        }
+
+        fn into_fatal(self) -> Self {
+            Self { fatal: true, ..self }
+        }
+
+        fn into_recoverable(self) -> Self {
+            Self { fatal: false, ..self }
+        }
+
+        fn add_context(mut self, context: &'static str) -> Self {
+            self.context.push(context);
+            self
+        }
+    }
+
+    fn declaration<'a, I: Input<Token = char, Slice = &'a str>>() -> impl Parser<I, Out = (&'a str, char)> {
+        string("let ")
+            .or(string("fn "))
+            .right(take_while1(|c| c.is_alphabetic()))
+            .then(char(';'))
+            .context("while parsing declaration")
+    }
+
+    #[test]
+    fn offset_error_test() {
+        let err = declaration().call(Text::<OffsetError>::with_errors("lex a;")).unwrap_err();
+        assert_eq!(OffsetError::new(0), err);
+
+        let err = declaration().call(Text::<OffsetError>::with_errors("fn a,")).unwrap_err();
+        assert_eq!(4, err.offset());
+
+        let err = declaration().call(Text::new("fn a,")).unwrap_err();
+        assert_eq!(&[Expected::Token(';')], err.expected());
+        assert_eq!(&["while parsing declaration"], err.context());
+
+        let err = be_u16().call(Bytes::<OffsetError>::with_errors(&[1])).unwrap_err();
+        assert_eq!(1, err.offset());
+    }
+
+    #[test]
+    fn user_error_test() {
+        let (result, _) = declaration().call(Text::<Contexts>::with_errors("let x;")).unwrap();
+        assert_eq!(("x", ';'), result);
+
+        let err = declaration().call(Text::<Contexts>::with_errors("let x")).unwrap_err();
+        assert_eq!(Contexts { offset: 5, fatal: false, context: vec!["while parsing declaration"] }, err);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{Input, ParseError, ParseResult, Parser};
+
+
+/// Runs `parser` without consuming any input.
+///
+/// Succeeds or fails exactly when `parser` does, but always hands back the
+/// input it was given.
+#[derive(Clone)]
+pub struct Peek<P> {
+    parser: P
+}
+
+
+impl<I: Input, P: Parser<I>> Parser<I> for Peek<P> {
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let (out, _) = self.parser.call(input.clone())?;
+        Ok((out, input))
+    }
+}
+
+
+pub fn peek<P>(parser: P) -> Peek<P> {
+    Peek { parser }
+}
+
+
+/// Succeeds without consuming input where `parser` fails, and fails where
+/// it succeeds.
+#[derive(Clone)]
+pub struct Not<P> {
+    parser: P
+}
+
+
+impl<I: Input, P: Parser<I>> Parser<I> for Not<P> {
+    type Out = ();
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        match self.parser.call(input.clone()) {
+            Ok(_) => Err(ParseError::from_unexpected(&input, None)),
+            Err(e) if e.is_fatal() => Err(e),
+            Err(_) => Ok(((), input)),
+        }
+    }
+}
+
+
+pub fn not<P>(parser: P) -> Not<P> {
+    Not { parser }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{char, eof, string, take_while1, Item, Position, Text};
+
+    #[test]
+    fn peek_test() {
+        let (result, rest) = peek(string("ab")).call(Text::new("abc")).unwrap();
+
+        assert_eq!("ab", result);
+        assert_eq!("abc", rest.as_str());
+
+        let err = peek(string("ab")).call(Text::new("ax")).unwrap_err();
+        assert_eq!(0, err.offset());
+    }
+
+    #[test]
+    fn not_test() {
+        let (_, rest) = not(char('a')).call(Text::new("ba")).unwrap();
+
+        assert_eq!("ba", rest.as_str());
+
+        let err = not(char('a')).call(Text::new("ab")).unwrap_err();
+        assert_eq!(Position::default(), err.position());
+        assert_eq!(Some('a'), err.found());
+    }
+
+    #[test]
+    fn keyword_test() {
+        let keyword = string("if").not_followed_by(take_while1(|c| c.is_alphanumeric()));
+
+        let (result, rest) = keyword.clone().call(Text::new("if x")).unwrap();
+        assert_eq!("if", result);
+        assert_eq!(" x", rest.as_str());
+
+        let err = keyword.call(Text::new("iffy")).unwrap_err();
+        assert_eq!(2, err.offset());
+    }
+
+    #[test]
+    fn followed_by_test() {
+        let (result, rest) =
+            Item::new()
+                .followed_by(eof())
+                .call(Text::new("a"))
+                .unwrap();
+
+        assert_eq!('a', result);
+        assert!(rest.is_empty());
+
+        assert!(Item::new().followed_by(eof()).call(Text::new("ab")).is_err());
+    }
+}
@@ -0,0 +1,533 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::{Input, ParseError, ParseResult, Parser, Position};
+
+
+static NEXT_RULE: AtomicUsize = AtomicUsize::new(0);
+
+
+/// Hit and miss counts of memoized rules.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    /// Calls answered from the memo.
+    pub hits: usize,
+    /// Calls that had to run their rule.
+    pub misses: usize,
+}
+
+
+impl MemoStats {
+    /// The share of memoized calls answered from the memo, between 0 and
+    /// 1, or 0 if there were none.
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            total => self.hits as f64 / total as f64,
+        }
+    }
+}
+
+
+// A result remembered in a table, with its output's type erased so that
+// rules with different outputs can share one table.
+type Remembered<I> = Result<(Box<dyn Any + Send>, I), <I as Input>::Error>;
+
+
+enum Entry<I: Input> {
+    /// The rule is running at this offset. `recursed` records whether it
+    /// has called itself there, i.e. whether it is left-recursive, and
+    /// `involved` the other memoized rules that were running in between,
+    /// whose results depend on its own.
+    InProgress { recursed: bool, involved: Vec<usize> },
+    Done(Remembered<I>),
+}
+
+
+struct Memos<I: Input> {
+    entries: HashMap<(usize, usize), Entry<I>>,
+    /// The rules that are running, as (rule, offset), innermost last.
+    running: Vec<(usize, usize)>,
+    stats: HashMap<usize, MemoStats>,
+}
+
+
+impl<I: Input> Memos<I> {
+    // Marks the rule running at `key` as left-recursive, along with every
+    // memoized rule that was called on the way from it back to itself.
+    fn recurse(&mut self, key: (usize, usize)) {
+        let start = self.running.iter().rposition(|&k| k == key).map_or(0, |i| i + 1);
+        let between: Vec<usize> = self.running[start..].iter().map(|&(rule, _)| rule).collect();
+        if let Some(Entry::InProgress { recursed, involved }) = self.entries.get_mut(&key) {
+            *recursed = true;
+            for rule in between {
+                if !involved.contains(&rule) {
+                    involved.push(rule);
+                }
+            }
+        }
+    }
+}
+
+
+/// The results of memoized rules during a single parse, handed to them
+/// through [`Packrat`] input.
+///
+/// Each rule marked with [`Parser::memo`] remembers its result at every
+/// offset it is tried at, keyed by the rule and the offset, so it runs at
+/// most once per position. The results live in the table rather than in
+/// the rules, so a grammar can be built once and used for any number of
+/// parses, each with a table of its own, even at the same time on
+/// different threads. They are dropped along with the table and every
+/// input made from it.
+pub struct MemoTable<I: Input> {
+    memos: Arc<Mutex<Memos<I>>>
+}
+
+
+impl<I: Input> MemoTable<I> {
+    pub fn new() -> Self {
+        let memos = Memos {
+            entries: HashMap::new(),
+            running: Vec::new(),
+            stats: HashMap::new()
+        };
+        Self { memos: Arc::new(Mutex::new(memos)) }
+    }
+
+    /// How often memoized rules were answered from this table, all rules
+    /// together; see [`Memo::stats`] for a single rule.
+    pub fn stats(&self) -> MemoStats {
+        self.lock().stats.values().fold(MemoStats::default(), |total, rule| MemoStats {
+            hits: total.hits + rule.hits,
+            misses: total.misses + rule.misses
+        })
+    }
+
+    // Another handle to the same table.
+    fn share(&self) -> Self {
+        Self { memos: Arc::clone(&self.memos) }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Memos<I>> {
+        self.memos.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+
+impl<I: Input> Default for MemoTable<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+impl<I: Input> fmt::Debug for MemoTable<I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoTable")
+            .field("entries", &self.lock().entries.len())
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+
+/// Input that carries a [`MemoTable`] along with it, which memoized rules
+/// need to run.
+#[derive(Debug)]
+pub struct Packrat<I: Input> {
+    input: I,
+    table: MemoTable<I>,
+}
+
+
+impl<I: Input> Packrat<I> {
+    pub fn new(input: I, table: &MemoTable<I>) -> Self {
+        Self {
+            input,
+            table: table.share()
+        }
+    }
+
+    /// The wrapped input.
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+}
+
+
+impl<I: Input> Clone for Packrat<I> {
+    fn clone(&self) -> Self {
+        Self::new(self.input.clone(), &self.table)
+    }
+}
+
+
+impl<I: Input> Input for Packrat<I> {
+    type Token = <I as Input>::Token;
+    type Slice = <I as Input>::Slice;
+    type Error = <I as Input>::Error;
+
+    fn next_token(&self) -> Option<(Self::Token, Self)> {
+        self.input
+            .next_token()
+            .map(|(t, input)| (t, Self::new(input, &self.table)))
+    }
+
+    fn slice_to(&self, end: &Self) -> Self::Slice {
+        self.input.slice_to(&end.input)
+    }
+
+    fn offset(&self) -> usize {
+        self.input.offset()
+    }
+
+    fn position(&self) -> Position {
+        self.input.position()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+}
+
+
+/// Remembers the result of `parser` at each offset for the current parse;
+/// see [`MemoTable`].
+///
+/// Only runs on [`Packrat`] input. The output must be `Clone`, since every
+/// hit hands out a copy of the remembered result, and `Send + 'static`,
+/// since the table holds the outputs of every rule together; map borrowed
+/// slices to owned values or [`Span`](crate::Span)s first. Clones of a
+/// rule share its memo.
+///
+/// A memoized rule may also be left-recursive, such as
+/// `expr = expr '+' term | term`, using seed growing after Warth et al.:
+/// when the rule calls itself at the offset it started at, that inner call
+/// fails, which leaves only the non-recursive alternatives to match. The
+/// rule is then run again and again with its last result as the answer to
+/// the inner call, each time matching one more step of the left-recursive
+/// alternative, until the match stops getting longer. The result is left
+/// associative. The recursion may also be indirect, through other rules;
+/// any of those that are memoized forget their results at that offset
+/// before every round, so that they see the latest one.
+#[derive(Clone)]
+pub struct Memo<P> {
+    parser: P,
+    rule: usize,
+}
+
+
+impl<P> Memo<P> {
+    pub fn new(parser: P) -> Self {
+        Self {
+            parser,
+            rule: NEXT_RULE.fetch_add(1, Ordering::Relaxed)
+        }
+    }
+
+    /// How often this rule was answered from its memo in `table`.
+    pub fn stats<I: Input>(&self, table: &MemoTable<I>) -> MemoStats {
+        table.lock().stats.get(&self.rule).copied().unwrap_or_default()
+    }
+}
+
+
+// Puts a result into the form kept in a table.
+fn remember<I, O>(result: &ParseResult<Packrat<I>, O>) -> Entry<I>
+where
+    I: Input,
+    O: Clone + Send + 'static
+{
+    let remembered = match result {
+        Ok((out, rest)) => Ok((Box::new(out.clone()) as Box<dyn Any + Send>, rest.input.clone())),
+        Err(e) => Err(e.clone()),
+    };
+    Entry::Done(remembered)
+}
+
+
+// Takes a result back out of a table.
+fn recall<I, O>(remembered: &Remembered<I>, table: &MemoTable<I>) -> ParseResult<Packrat<I>, O>
+where
+    I: Input,
+    O: Clone + 'static
+{
+    match remembered {
+        Ok((out, rest)) => {
+            let out = out.downcast_ref::<O>().expect("memo entry of another type");
+            Ok((out.clone(), Packrat::new(rest.clone(), table)))
+        }
+        Err(e) => Err(e.clone()),
+    }
+}
+
+
+impl<I, P> Parser<Packrat<I>> for Memo<P>
+where
+    I: Input,
+    P: Parser<Packrat<I>>,
+    <P as Parser<Packrat<I>>>::Out: Clone + Send + 'static
+{
+    type Out = <P as Parser<Packrat<I>>>::Out;
+
+    fn call(&self, input: Packrat<I>) -> ParseResult<Packrat<I>, Self::Out> {
+        let table = &input.table;
+        let key = (self.rule, input.offset());
+        {
+            let mut memos = table.lock();
+            match memos.entries.get_mut(&key) {
+                Some(Entry::Done(remembered)) => {
+                    let result = recall(remembered, table);
+                    memos.stats.entry(self.rule).or_default().hits += 1;
+                    return result;
+                }
+                Some(Entry::InProgress { .. }) => {
+                    memos.recurse(key);
+                    return Err(ParseError::from_unexpected(&input, None));
+                }
+                None => {
+                    let entry = Entry::InProgress { recursed: false, involved: Vec::new() };
+                    memos.entries.insert(key, entry);
+                    memos.running.push(key);
+                    memos.stats.entry(self.rule).or_default().misses += 1;
+                }
+            }
+        }
+
+        // The table is not locked while the rule runs, since it may well
+        // call itself.
+        let mut result = self.parser.call(input.clone());
+
+        let involved = {
+            let mut memos = table.lock();
+            memos.running.pop();
+            match memos.entries.remove(&key) {
+                Some(Entry::InProgress { recursed: true, involved }) => Some(involved),
+                _ => None,
+            }
+        };
+        if let Some(involved) = involved {
+            loop {
+                {
+                    let mut memos = table.lock();
+                    memos.entries.insert(key, remember(&result));
+                    for &rule in &involved {
+                        memos.entries.remove(&(rule, key.1));
+                    }
+                }
+                let next = self.parser.call(input.clone());
+                match (&next, &result) {
+                    (Ok((_, end)), Ok((_, seed))) if end.offset() > seed.offset() => result = next,
+                    _ => break,
+                }
+            }
+        }
+
+        table.lock().entries.insert(key, remember(&result));
+        result
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{char, choice, recursive, recursive_sync, satisfy, Expected, Text};
+    use std::sync::Arc;
+
+    // expr = term '+' expr | term '-' expr | term
+    // term = digit | '(' expr ')'
+    //
+    // Without memoization every level of parentheses parses `term` three
+    // times, so the work grows as 3^depth.
+    fn count_digits(memoize: bool, source: &str) -> (Result<i64, ()>, usize, MemoStats) {
+        let digits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&digits);
+
+        let expr = recursive(|expr| {
+            let digit = satisfy(move |c| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                c.is_ascii_digit()
+            });
+            let term = choice((
+                digit.map(|c| i64::from(c.to_digit(10).unwrap())),
+                char('(').right(expr.clone()).left(char(')')),
+            ));
+            let term = if memoize { term.memo().boxed() } else { term.boxed() };
+
+            choice((
+                term.clone().left(char('+')).then(expr.clone()).map(|(a, b)| a + b),
+                term.clone().left(char('-')).then(expr).map(|(a, b)| a - b),
+                term,
+            ))
+        });
+
+        let table = MemoTable::new();
+        let result = expr.call(Packrat::new(Text::new(source), &table));
+        (result.map(|(n, _)| n).map_err(|_| ()), digits.load(Ordering::Relaxed), table.stats())
+    }
+
+    #[test]
+    fn memo_test() {
+        let source = "((((((((1-2))))))))";
+
+        let (plain, plain_digits, plain_stats) = count_digits(false, source);
+        let (memo, memo_digits, memo_stats) = count_digits(true, source);
+
+        assert_eq!(Ok(-1), plain);
+        assert_eq!(plain, memo);
+        assert!(plain_digits > 1000);
+        assert!(memo_digits < 30);
+        assert_eq!(MemoStats::default(), plain_stats);
+        assert!(memo_stats.hits > 0);
+        assert!(memo_stats.hit_rate() > 0.5);
+    }
+
+    #[test]
+    fn memo_table_test() {
+        let word = satisfy(|c: char| c.is_alphabetic()).memo();
+        let letter = word.clone();
+
+        let first = MemoTable::new();
+        let (result, _) = word.call(Packrat::new(Text::new("a"), &first)).unwrap();
+        assert_eq!('a', result);
+
+        let second = MemoTable::new();
+        let (result, _) = word.call(Packrat::new(Text::new("b"), &second)).unwrap();
+        assert_eq!('b', result);
+
+        let (result, _) = letter.call(Packrat::new(Text::new("b"), &second)).unwrap();
+        assert_eq!('b', result);
+
+        assert_eq!(MemoStats { hits: 0, misses: 1 }, first.stats());
+        assert_eq!(MemoStats { hits: 1, misses: 1 }, word.stats(&second));
+        assert_eq!(MemoStats::default(), word.stats(&MemoTable::<Text>::new()));
+    }
+
+    #[test]
+    fn concurrent_parses_test() {
+        // expr = expr '-' digit | digit, built once and shared by every
+        // thread, with a table of its own for each parse.
+        let expr = recursive_sync(|expr| {
+            let digit = satisfy(|c| c.is_ascii_digit()).map(|c| c.to_digit(10).unwrap() as i64);
+            choice((expr.left(char('-')).then(digit.clone()).map(|(a, b)| a - b), digit)).memo()
+        });
+        let expr = Arc::new(expr);
+
+        let workers: Vec<_> = (0..8)
+            .map(|_| {
+                let expr = Arc::clone(&expr);
+                std::thread::spawn(move || {
+                    (0..200)
+                        .map(|_| {
+                            let table = MemoTable::new();
+                            let result = expr.call(Packrat::new(Text::new("9-1-2-3-1"), &table));
+                            result.map(|(n, _)| n).ok()
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            assert!(worker.join().unwrap().iter().all(|&n| n == Some(2)));
+        }
+    }
+
+    #[test]
+    fn packrat_input_test() {
+        let table = MemoTable::new();
+        let input = Packrat::new(Text::new("ab"), &table);
+
+        let (c, rest) = input.next_token().unwrap();
+
+        assert_eq!('a', c);
+        assert_eq!("b", rest.inner().as_str());
+        assert_eq!("a", input.slice_to(&rest));
+        assert_eq!(Position::new(1, 1, 2), rest.position());
+    }
+
+    #[test]
+    fn left_recursion_test() {
+        // expr = expr '-' digit | digit
+        let expr = recursive(|expr| {
+            let digit = satisfy(|c| c.is_ascii_digit()).map(|c| c.to_string());
+            choice((
+                expr.left(char('-')).then(digit.clone()).map(|(a, b)| format!("({}-{})", a, b)),
+                digit,
+            ))
+            .memo()
+        });
+        let table = MemoTable::new();
+
+        let (result, rest) =
+            expr
+                .call(Packrat::new(Text::new("7-2-1+"), &table))
+                .unwrap();
+
+        assert_eq!("((7-2)-1)", result);
+        assert_eq!("+", rest.inner().as_str());
+    }
+
+    #[test]
+    fn indirect_left_recursion_test() {
+        // sum = operand '+' digit | digit, operand = sum
+        let sum = recursive(|sum| {
+            let digit = satisfy(|c| c.is_ascii_digit()).map(|c| c.to_digit(10).unwrap());
+            let operand = sum.map(|n| n * 10);
+            choice((
+                operand.left(char('+')).then(digit.clone()).map(|(a, b)| a + b),
+                digit,
+            ))
+            .memo()
+        });
+        let table = MemoTable::new();
+
+        let (result, _) =
+            sum
+                .call(Packrat::new(Text::new("1+2+3"), &table))
+                .unwrap();
+
+        assert_eq!(123, result);
+    }
+
+    #[test]
+    fn memoized_middle_rule_test() {
+        // sum = operand '+' digit | digit, operand = sum, both memoized
+        let sum = recursive(|sum| {
+            let digit = satisfy(|c| c.is_ascii_digit()).map(|c| c.to_digit(10).unwrap());
+            let operand = sum.map(|n| n * 10).memo();
+            choice((
+                operand.left(char('+')).then(digit.clone()).map(|(a, b)| a + b),
+                digit,
+            ))
+            .memo()
+        });
+        let table = MemoTable::new();
+
+        let (result, rest) =
+            sum
+                .call(Packrat::new(Text::new("1+2+3"), &table))
+                .unwrap();
+
+        assert_eq!(123, result);
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn left_recursion_error_test() {
+        let expr = recursive(|expr| {
+            choice((expr.left(char('+')).then(char('x')).map(|_| 'e'), char('x'))).memo()
+        });
+        let table = MemoTable::new();
+
+        let err = expr.call(Packrat::new(Text::new("y"), &table)).unwrap_err();
+
+        assert_eq!(0, err.offset());
+        assert_eq!(&[Expected::Token('x')], err.expected());
+    }
+}
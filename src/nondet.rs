@@ -0,0 +1,274 @@
+use std::iter;
+
+use crate::{Bind, Input, Item, Left, Map, Or, ParseResult, Parser, Return, Right, Then, Zero};
+
+
+/// A parser that reports every way it can match instead of only the first.
+///
+/// This is the list-of-successes reading of Hutton & Meijer's "Monadic
+/// Parser Combinators": [`Item`], [`Zero`] and [`Return`] give at most one
+/// parse, [`Bind`] runs its continuation on each parse of its first parser,
+/// and [`Or`] gives the parses of both sides. Calling a parser through
+/// [`Parser::call`] instead still commits to the first success. Other
+/// parsers can be lifted in with [`once`].
+///
+/// The parses are worked out one at a time as they are asked for, so
+/// taking the first few of a very ambiguous parse costs no more than
+/// finding those few.
+pub trait NondetParser<I: Input>: Parser<I> {
+    /// Every successful parse, in order, each with the input left after it.
+    fn into_parses(self, input: I) -> impl Iterator<Item = (Self::Out, I)>
+    where
+        Self: Sized;
+
+    /// Like [`into_parses`](NondetParser::into_parses), on a clone of
+    /// `self`.
+    fn parses(&self, input: I) -> impl Iterator<Item = (Self::Out, I)>
+    where
+        Self: Sized + Clone
+    {
+        self.clone().into_parses(input)
+    }
+}
+
+
+// The parses of a parser that can match in at most one way, which is only
+// run once the parse is asked for.
+fn single<I: Input, P: Parser<I>>(parser: P, input: I) -> impl Iterator<Item = (P::Out, I)> {
+    iter::once_with(move || parser.call(input)).filter_map(ParseResult::ok)
+}
+
+
+impl<I: Input> NondetParser<I> for Item<I> {
+    fn into_parses(self, input: I) -> impl Iterator<Item = (Self::Out, I)> {
+        single(self, input)
+    }
+}
+
+
+impl<A, I: Input> NondetParser<I> for Zero<A, I> {
+    fn into_parses(self, _: I) -> impl Iterator<Item = (Self::Out, I)> {
+        iter::empty()
+    }
+}
+
+
+impl<A: Clone, I: Input> NondetParser<I> for Return<A, I> {
+    fn into_parses(self, input: I) -> impl Iterator<Item = (Self::Out, I)> {
+        single(self, input)
+    }
+}
+
+
+impl<I: Input, P, F, A> NondetParser<I> for Map<P, F>
+where
+    P: NondetParser<I>,
+    F: Fn(<P as Parser<I>>::Out) -> A
+{
+    fn into_parses(self, input: I) -> impl Iterator<Item = (Self::Out, I)> {
+        let func = self.func;
+        self.parser
+            .into_parses(input)
+            .map(move |(a, rest)| (func(a), rest))
+    }
+}
+
+
+impl<I: Input, P, F, Q> NondetParser<I> for Bind<P, F>
+where
+    P: NondetParser<I>,
+    Q: NondetParser<I>,
+    F: Fn(<P as Parser<I>>::Out) -> Q
+{
+    fn into_parses(self, input: I) -> impl Iterator<Item = (Self::Out, I)> {
+        let func = self.func;
+        self.parser
+            .into_parses(input)
+            .flat_map(move |(a, rest)| func(a).into_parses(rest))
+    }
+}
+
+
+impl<I: Input, A, B> NondetParser<I> for Or<A, B>
+where
+    A: NondetParser<I>,
+    B: NondetParser<I, Out = <A as Parser<I>>::Out>
+{
+    fn into_parses(self, input: I) -> impl Iterator<Item = (Self::Out, I)> {
+        // `right` isn't started until every parse of `left` has been taken.
+        let right =
+            iter::once((self.right, input.clone()))
+                .flat_map(|(right, input)| right.into_parses(input));
+        self.left.into_parses(input).chain(right)
+    }
+}
+
+
+impl<I: Input, A, B> NondetParser<I> for Then<A, B>
+where
+    A: NondetParser<I>,
+    B: NondetParser<I> + Clone,
+    <A as Parser<I>>::Out: Clone
+{
+    fn into_parses(self, input: I) -> impl Iterator<Item = (Self::Out, I)> {
+        let second = self.second;
+        self.first.into_parses(input).flat_map(move |(a, rest)| {
+            second
+                .clone()
+                .into_parses(rest)
+                .map(move |(b, rest)| ((a.clone(), b), rest))
+        })
+    }
+}
+
+
+impl<I: Input, A, B> NondetParser<I> for Left<A, B>
+where
+    A: NondetParser<I>,
+    B: NondetParser<I> + Clone,
+    <A as Parser<I>>::Out: Clone
+{
+    fn into_parses(self, input: I) -> impl Iterator<Item = (Self::Out, I)> {
+        let second = self.second;
+        self.first.into_parses(input).flat_map(move |(a, rest)| {
+            second
+                .clone()
+                .into_parses(rest)
+                .map(move |(_, rest)| (a.clone(), rest))
+        })
+    }
+}
+
+
+impl<I: Input, A, B> NondetParser<I> for Right<A, B>
+where
+    A: NondetParser<I>,
+    B: NondetParser<I> + Clone
+{
+    fn into_parses(self, input: I) -> impl Iterator<Item = (Self::Out, I)> {
+        let second = self.second;
+        self.first
+            .into_parses(input)
+            .flat_map(move |(_, rest)| second.clone().into_parses(rest))
+    }
+}
+
+
+/// Lifts a parser into [`NondetParser`] as having at most one parse, its
+/// usual result.
+#[derive(Clone)]
+pub struct Once<P> {
+    parser: P
+}
+
+
+impl<I: Input, P: Parser<I>> Parser<I> for Once<P> {
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        self.parser.call(input)
+    }
+}
+
+
+impl<I: Input, P: Parser<I>> NondetParser<I> for Once<P> {
+    fn into_parses(self, input: I) -> impl Iterator<Item = (Self::Out, I)> {
+        single(self.parser, input)
+    }
+}
+
+
+pub fn once<P>(parser: P) -> Once<P> {
+    Once { parser }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use crate::{eof, pure, string, Text};
+
+    #[test]
+    fn item_zero_return_test() {
+        let results: Vec<_> = Item::new().parses(Text::new("ab")).collect();
+        assert_eq!(1, results.len());
+        assert_eq!('a', results[0].0);
+
+        assert_eq!(0, Zero::<char, _>::new().parses(Text::new("ab")).count());
+        assert_eq!(0, Item::new().parses(Text::new("")).count());
+        assert_eq!(1, pure(()).parses(Text::new("")).count());
+    }
+
+    #[test]
+    fn or_test() {
+        let results: Vec<_> =
+            Item::new()
+                .or(pure('z'))
+                .parses(Text::new("ab"))
+                .map(|(c, rest)| (c, rest.as_str()))
+                .collect();
+
+        assert_eq!(vec![('a', "b"), ('z', "ab")], results);
+    }
+
+    #[test]
+    fn ambiguous_split_test() {
+        let run = || once(string("a")).or(once(string("aa")));
+
+        let splits: Vec<_> =
+            run()
+                .then(run())
+                .left(once(eof()))
+                .parses(Text::new("aaa"))
+                .map(|(split, _)| split)
+                .collect();
+
+        assert_eq!(vec![("a", "aa"), ("aa", "a")], splits);
+    }
+
+    #[test]
+    fn bind_test() {
+        // Every way to read a prefix of "ab" as one or two items.
+        let prefixes: Vec<_> =
+            Item::new()
+                .bind(|a: char| Item::new().map(move |b| format!("{}{}", a, b)).or(pure(a.to_string())))
+                .parses(Text::new("ab"))
+                .map(|(s, _)| s)
+                .collect();
+
+        assert_eq!(vec!["ab", "a"], prefixes);
+    }
+
+    #[test]
+    fn first_parse_test() {
+        let parser = pure('z').or(Item::new());
+
+        assert_eq!('z', parser.call(Text::new("ab")).unwrap().0);
+        assert_eq!(2, parser.parses(Text::new("ab")).count());
+    }
+
+    #[test]
+    fn first_of_many_parses_test() {
+        fn twice<P: Clone>(parser: P) -> Then<P, P> {
+            Then::new(parser.clone(), parser)
+        }
+
+        let calls = Rc::new(Cell::new(0));
+        let counted = calls.clone();
+        let either = Item::new().or(Item::new()).map(move |c| {
+            counted.set(counted.get() + 1);
+            c
+        });
+
+        // 32 items with two ways to read each, so 2^32 parses in all.
+        let all = twice(twice(twice(twice(twice(either)))));
+        let input = Text::new("abcdefghijklmnopqrstuvwxyz012345");
+
+        let (_, rest) = all.parses(input).next().unwrap();
+
+        assert_eq!("", rest.as_str());
+        assert_eq!(32, calls.get());
+    }
+}
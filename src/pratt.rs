@@ -0,0 +1,511 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::{BoxedParser, ErrorKind, Input, ParseError, ParseResult, Parser, SyncBoxedParser};
+
+
+/// How a run of infix operators of the same precedence groups.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Assoc {
+    /// `a - b - c` is `(a - b) - c`.
+    Left,
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`.
+    Right,
+}
+
+
+/// What a [`Pratt`] parser keeps its parts in, named by the type it keeps
+/// its operand in: [`BoxedParser`] takes any parts, and [`SyncBoxedParser`]
+/// only `Send + Sync` ones, which makes the whole parser `Send + Sync`.
+pub trait PrattParts<'a, I: Input, O>: Parser<I, Out = O> + Clone {
+    /// An operator's token, with its output dropped.
+    type Token: Parser<I, Out = ()> + Clone;
+    type Unary: Clone;
+    type Binary: Clone;
+    type Ternary: Clone;
+
+    fn unary(build: &Self::Unary, operand: O) -> O;
+
+    fn binary(build: &Self::Binary, left: O, right: O) -> O;
+
+    fn ternary(build: &Self::Ternary, first: O, second: O, third: O) -> O;
+}
+
+
+impl<'a, I: Input, O> PrattParts<'a, I, O> for BoxedParser<'a, I, O> {
+    type Token = BoxedParser<'a, I, ()>;
+    type Unary = Rc<dyn Fn(O) -> O + 'a>;
+    type Binary = Rc<dyn Fn(O, O) -> O + 'a>;
+    type Ternary = Rc<dyn Fn(O, O, O) -> O + 'a>;
+
+    fn unary(build: &Self::Unary, operand: O) -> O {
+        build(operand)
+    }
+
+    fn binary(build: &Self::Binary, left: O, right: O) -> O {
+        build(left, right)
+    }
+
+    fn ternary(build: &Self::Ternary, first: O, second: O, third: O) -> O {
+        build(first, second, third)
+    }
+}
+
+
+impl<'a, I: Input, O> PrattParts<'a, I, O> for SyncBoxedParser<'a, I, O> {
+    type Token = SyncBoxedParser<'a, I, ()>;
+    type Unary = Arc<dyn Fn(O) -> O + Send + Sync + 'a>;
+    type Binary = Arc<dyn Fn(O, O) -> O + Send + Sync + 'a>;
+    type Ternary = Arc<dyn Fn(O, O, O) -> O + Send + Sync + 'a>;
+
+    fn unary(build: &Self::Unary, operand: O) -> O {
+        build(operand)
+    }
+
+    fn binary(build: &Self::Binary, left: O, right: O) -> O {
+        build(left, right)
+    }
+
+    fn ternary(build: &Self::Ternary, first: O, second: O, third: O) -> O {
+        build(first, second, third)
+    }
+}
+
+
+// An operator's token, how strongly it holds on to the operands to its
+// left and right, and how it builds its output from them.
+#[derive(Clone)]
+struct Operator<T, F> {
+    token: T,
+    left: u64,
+    right: u64,
+    build: F,
+}
+
+
+type Token<'a, I, O, B> = <B as PrattParts<'a, I, O>>::Token;
+type Unary<'a, I, O, B> = Operator<Token<'a, I, O, B>, <B as PrattParts<'a, I, O>>::Unary>;
+type Binary<'a, I, O, B> = Operator<Token<'a, I, O, B>, <B as PrattParts<'a, I, O>>::Binary>;
+
+// A ternary's closing token goes along with how it builds its output.
+type Ternary<'a, I, O, B> =
+    Operator<Token<'a, I, O, B>, (Token<'a, I, O, B>, <B as PrattParts<'a, I, O>>::Ternary)>;
+
+
+// Every precedence level gets two binding powers, so that associativity
+// can decide between two operators of the same level.
+fn binding(precedence: u32, assoc: Assoc) -> (u64, u64) {
+    let low = 2 * u64::from(precedence) + 1;
+    match assoc {
+        Assoc::Left => (low, low + 1),
+        Assoc::Right => (low + 1, low),
+    }
+}
+
+
+type Found<'o, I, T, F> = Option<(&'o Operator<T, F>, I)>;
+
+
+// The first of `ops` whose token matches at `input`, if any. Non-fatal
+// failures of the others are merged into `missed`.
+fn find<'o, I: Input, T: Parser<I>, F>(
+    ops: &'o [Operator<T, F>],
+    input: &I,
+    missed: &mut Option<<I as Input>::Error>
+) -> Result<Found<'o, I, T, F>, <I as Input>::Error> {
+    for op in ops {
+        match op.token.call(input.clone()) {
+            Ok((_, rest)) if rest.offset() == input.offset() => {
+                return Err(ParseError::from_kind(input, ErrorKind::NoProgress));
+            }
+            Ok((_, rest)) => return Ok(Some((op, rest))),
+            Err(e) if e.is_fatal() => return Err(e),
+            Err(e) => {
+                *missed = Some(match missed.take() {
+                    Some(m) => m.merge(e),
+                    None => e,
+                });
+            }
+        }
+    }
+    Ok(None)
+}
+
+
+/// An expression parser driven by a table of operators, built up with
+/// [`prefix`](Pratt::prefix), [`infix`](Pratt::infix),
+/// [`postfix`](Pratt::postfix) and [`ternary`](Pratt::ternary).
+///
+/// Operands are parsed by `operand`; everything between them is looked up
+/// in the table, so adding a precedence level is one more call instead of
+/// one more layer of [`chainl1`](crate::chainl1). Higher precedences bind
+/// tighter. On a tie, a prefix operator takes in a right-associative infix
+/// operator after its operand but not a left-associative one, and is
+/// applied before a postfix operator: with `-` and `^` on one level,
+/// `-a^b` is `-(a^b)`, and `-a!` is `(-a)!`.
+///
+/// Operators are tried in the order they were added. Once one matches, the
+/// operands it needs are no longer optional: `1 +` is an error rather than
+/// `1` followed by a stray `+`.
+///
+/// The parts are kept as `B`; see [`PrattParts`]. One built with
+/// [`pratt_sync`] can be shared between threads.
+pub struct Pratt<'a, I: Input, O, B: PrattParts<'a, I, O> = BoxedParser<'a, I, O>> {
+    operand: B,
+    prefix: Vec<Unary<'a, I, O, B>>,
+    postfix: Vec<Unary<'a, I, O, B>>,
+    infix: Vec<Binary<'a, I, O, B>>,
+    ternary: Vec<Ternary<'a, I, O, B>>,
+}
+
+
+impl<'a, I: Input, O> Pratt<'a, I, O> {
+    pub fn new<P>(operand: P) -> Self
+    where
+        P: Parser<I, Out = O> + 'a
+    {
+        Self::with_operand(operand.boxed())
+    }
+
+    /// Adds a prefix operator, which `build`s its output from the operand
+    /// after it.
+    pub fn prefix<P, F>(self, op: P, precedence: u32, build: F) -> Self
+    where
+        P: Parser<I> + 'a,
+        F: Fn(O) -> O + 'a
+    {
+        self.add_prefix(op.map(|_| ()).boxed(), precedence, Rc::new(build))
+    }
+
+    /// Adds an infix operator, which `build`s its output from the operands
+    /// on either side of it.
+    pub fn infix<P, F>(self, op: P, precedence: u32, assoc: Assoc, build: F) -> Self
+    where
+        P: Parser<I> + 'a,
+        F: Fn(O, O) -> O + 'a
+    {
+        self.add_infix(op.map(|_| ()).boxed(), precedence, assoc, Rc::new(build))
+    }
+
+    /// Adds a postfix operator, which `build`s its output from the operand
+    /// before it.
+    pub fn postfix<P, F>(self, op: P, precedence: u32, build: F) -> Self
+    where
+        P: Parser<I> + 'a,
+        F: Fn(O) -> O + 'a
+    {
+        self.add_postfix(op.map(|_| ()).boxed(), precedence, Rc::new(build))
+    }
+
+    /// Adds a ternary operator such as `c ? a : b`, which `build`s its
+    /// output from the three operands in order.
+    ///
+    /// Any expression may appear between `open` and `close`. Ternaries are
+    /// right-associative, so `a ? b : c ? d : e` is `a ? b : (c ? d : e)`.
+    pub fn ternary<P, Q, F>(self, open: P, close: Q, precedence: u32, build: F) -> Self
+    where
+        P: Parser<I> + 'a,
+        Q: Parser<I> + 'a,
+        F: Fn(O, O, O) -> O + 'a
+    {
+        let (open, close) = (open.map(|_| ()).boxed(), close.map(|_| ()).boxed());
+        self.add_ternary(open, close, precedence, Rc::new(build))
+    }
+}
+
+
+/// The same builder methods as for any [`Pratt`], for `Send + Sync` parts.
+impl<'a, I: Input, O> Pratt<'a, I, O, SyncBoxedParser<'a, I, O>> {
+    pub fn new<P>(operand: P) -> Self
+    where
+        P: Parser<I, Out = O> + Send + Sync + 'a
+    {
+        Self::with_operand(operand.boxed_sync())
+    }
+
+    pub fn prefix<P, F>(self, op: P, precedence: u32, build: F) -> Self
+    where
+        P: Parser<I> + Send + Sync + 'a,
+        F: Fn(O) -> O + Send + Sync + 'a
+    {
+        self.add_prefix(op.map(|_| ()).boxed_sync(), precedence, Arc::new(build))
+    }
+
+    pub fn infix<P, F>(self, op: P, precedence: u32, assoc: Assoc, build: F) -> Self
+    where
+        P: Parser<I> + Send + Sync + 'a,
+        F: Fn(O, O) -> O + Send + Sync + 'a
+    {
+        self.add_infix(op.map(|_| ()).boxed_sync(), precedence, assoc, Arc::new(build))
+    }
+
+    pub fn postfix<P, F>(self, op: P, precedence: u32, build: F) -> Self
+    where
+        P: Parser<I> + Send + Sync + 'a,
+        F: Fn(O) -> O + Send + Sync + 'a
+    {
+        self.add_postfix(op.map(|_| ()).boxed_sync(), precedence, Arc::new(build))
+    }
+
+    pub fn ternary<P, Q, F>(self, open: P, close: Q, precedence: u32, build: F) -> Self
+    where
+        P: Parser<I> + Send + Sync + 'a,
+        Q: Parser<I> + Send + Sync + 'a,
+        F: Fn(O, O, O) -> O + Send + Sync + 'a
+    {
+        let (open, close) = (open.map(|_| ()).boxed_sync(), close.map(|_| ()).boxed_sync());
+        self.add_ternary(open, close, precedence, Arc::new(build))
+    }
+}
+
+
+impl<'a, I: Input, O, B: PrattParts<'a, I, O>> Pratt<'a, I, O, B> {
+    fn with_operand(operand: B) -> Self {
+        Self {
+            operand,
+            prefix: Vec::new(),
+            postfix: Vec::new(),
+            infix: Vec::new(),
+            ternary: Vec::new()
+        }
+    }
+
+    fn add_prefix(mut self, token: B::Token, precedence: u32, build: B::Unary) -> Self {
+        let (_, right) = binding(precedence, Assoc::Left);
+        self.prefix.push(Operator { token, left: 0, right, build });
+        self
+    }
+
+    fn add_infix(mut self, token: B::Token, precedence: u32, assoc: Assoc, build: B::Binary) -> Self {
+        let (left, right) = binding(precedence, assoc);
+        self.infix.push(Operator { token, left, right, build });
+        self
+    }
+
+    fn add_postfix(mut self, token: B::Token, precedence: u32, build: B::Unary) -> Self {
+        let (left, _) = binding(precedence, Assoc::Left);
+        self.postfix.push(Operator { token, left, right: 0, build });
+        self
+    }
+
+    fn add_ternary(mut self, open: B::Token, close: B::Token, precedence: u32, build: B::Ternary) -> Self {
+        let (left, right) = binding(precedence, Assoc::Right);
+        self.ternary.push(Operator { token: open, left, right, build: (close, build) });
+        self
+    }
+
+    // Parses an expression whose operators all hold on to their left
+    // operand at least as strongly as `min`.
+    fn expression(&self, input: I, min: u64) -> ParseResult<I, O> {
+        let mut missed = None;
+        let (mut lhs, mut rest) = match find(&self.prefix, &input, &mut missed)? {
+            Some((op, rest)) => {
+                let (operand, rest) = self.expression(rest, op.right)?;
+                (B::unary(&op.build, operand), rest)
+            }
+            None => self.operand.call(input).map_err(|e| match missed {
+                Some(m) if !e.is_fatal() => e.merge(m),
+                _ => e,
+            })?,
+        };
+
+        loop {
+            if let Some((op, after)) = find(&self.postfix, &rest, &mut None)? {
+                if op.left < min {
+                    break;
+                }
+                lhs = B::unary(&op.build, lhs);
+                rest = after;
+            } else if let Some((op, after)) = find(&self.infix, &rest, &mut None)? {
+                if op.left < min {
+                    break;
+                }
+                let (rhs, after) = self.expression(after, op.right)?;
+                lhs = B::binary(&op.build, lhs, rhs);
+                rest = after;
+            } else if let Some((op, after)) = find(&self.ternary, &rest, &mut None)? {
+                if op.left < min {
+                    break;
+                }
+                let (close, build) = &op.build;
+                let (middle, after) = self.expression(after, 0)?;
+                let (_, after) = close.call(after)?;
+                let (rhs, after) = self.expression(after, op.right)?;
+                lhs = B::ternary(build, lhs, middle, rhs);
+                rest = after;
+            } else {
+                break;
+            }
+        }
+        Ok((lhs, rest))
+    }
+}
+
+
+impl<'a, I: Input, O, B: PrattParts<'a, I, O>> Clone for Pratt<'a, I, O, B> {
+    fn clone(&self) -> Self {
+        Self {
+            operand: self.operand.clone(),
+            prefix: self.prefix.clone(),
+            postfix: self.postfix.clone(),
+            infix: self.infix.clone(),
+            ternary: self.ternary.clone()
+        }
+    }
+}
+
+
+impl<'a, I: Input, O, B: PrattParts<'a, I, O>> Parser<I> for Pratt<'a, I, O, B> {
+    type Out = O;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        self.expression(input, 0)
+    }
+}
+
+
+pub fn pratt<'a, I, O, P>(operand: P) -> Pratt<'a, I, O>
+where
+    I: Input,
+    P: Parser<I, Out = O> + 'a
+{
+    Pratt::<'a, I, O>::new(operand)
+}
+
+
+/// Like [`pratt`], for `Send + Sync` parts, so that the parser can be
+/// shared between threads.
+pub fn pratt_sync<'a, I, O, P>(operand: P) -> Pratt<'a, I, O, SyncBoxedParser<'a, I, O>>
+where
+    I: Input,
+    P: Parser<I, Out = O> + Send + Sync + 'a
+{
+    Pratt::<'a, I, O, SyncBoxedParser<'a, I, O>>::new(operand)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{char, cut, recursive, recursive_sync, satisfy, Expected, Text};
+
+    fn unary(op: &'static str) -> impl Fn(String) -> String {
+        move |x| format!("({}{})", op, x)
+    }
+
+    fn binary(op: &'static str) -> impl Fn(String, String) -> String {
+        move |a, b| format!("({}{}{})", a, op, b)
+    }
+
+    // ?: < + - < * / < prefix - and ^ < postfix !
+    fn expression<'a>() -> impl Parser<Text<'a>, Out = String> {
+        recursive(|expr| {
+            let atom = satisfy(|c| c.is_ascii_alphanumeric()).map(String::from);
+            let group = char('(').right(expr).left(char(')'));
+
+            pratt(atom.or(group))
+                .ternary(char('?'), char(':'), 0, |c, a, b| format!("({}?{}:{})", c, a, b))
+                .infix(char('+'), 1, Assoc::Left, binary("+"))
+                .infix(char('-'), 1, Assoc::Left, binary("-"))
+                .infix(char('*'), 2, Assoc::Left, binary("*"))
+                .infix(char('/'), 2, Assoc::Left, binary("/"))
+                .prefix(char('-'), 3, unary("-"))
+                .infix(char('^'), 3, Assoc::Right, binary("^"))
+                .postfix(char('!'), 4, |x| format!("({}!)", x))
+        })
+    }
+
+    fn parse(source: &str) -> String {
+        let (result, rest) = expression().call(Text::new(source)).unwrap();
+        assert!(rest.is_empty(), "{} left {:?}", source, rest.as_str());
+        result
+    }
+
+    #[test]
+    fn precedence_test() {
+        assert_eq!("((1+(2*3))-4)", parse("1+2*3-4"));
+        assert_eq!("((1+2)*3)", parse("(1+2)*3"));
+        assert_eq!("((a/b)/c)", parse("a/b/c"));
+    }
+
+    #[test]
+    fn associativity_test() {
+        assert_eq!("(2^(3^2))", parse("2^3^2"));
+        assert_eq!("((a-b)-c)", parse("a-b-c"));
+    }
+
+    #[test]
+    fn prefix_postfix_test() {
+        assert_eq!("(-(2^2))", parse("-2^2"));
+        assert_eq!("((-2)*3)", parse("-2*3"));
+        assert_eq!("(-(-a))", parse("--a"));
+        assert_eq!("((-(3!))-1)", parse("-3!-1"));
+        assert_eq!("((n!)!)", parse("n!!"));
+    }
+
+    #[test]
+    fn ternary_test() {
+        assert_eq!("(a?b:(c?d:e))", parse("a?b:c?d:e"));
+        assert_eq!("((1+2)?(x?y:z):(3*4))", parse("1+2?x?y:z:3*4"));
+    }
+
+    #[test]
+    fn error_test() {
+        let err = expression().call(Text::new("1+")).unwrap_err();
+        assert_eq!(2, err.offset());
+        assert_eq!(None, err.found());
+
+        let err = expression().call(Text::new("a?b;")).unwrap_err();
+        assert_eq!(3, err.offset());
+        assert_eq!(&[Expected::Token(':')], err.expected());
+    }
+
+    #[test]
+    fn operand_error_test() {
+        let err = pratt(char('x')).prefix(char('-'), 0, |x| x).call(Text::new("?")).unwrap_err();
+
+        assert_eq!(0, err.offset());
+        assert_eq!(&[Expected::Token('x'), Expected::Token('-')], err.expected());
+    }
+
+    #[test]
+    fn fatal_operand_error_test() {
+        // The prefix token gets further than the operand, but the
+        // operand's failure is the fatal one.
+        let err =
+            pratt(cut(char('y')))
+                .prefix(char('-').right(char('-')), 0, |x| x)
+                .or(char('-'))
+                .call(Text::new("-x"))
+                .unwrap_err();
+
+        assert!(err.is_fatal());
+        assert_eq!(0, err.offset());
+    }
+
+    #[test]
+    fn share_between_threads_test() {
+        let expression = recursive_sync(|expr| {
+            let atom = satisfy(|c| c.is_ascii_alphanumeric()).map(String::from);
+            let group = char('(').right(expr).left(char(')'));
+
+            pratt_sync(atom.or(group))
+                .infix(char('+'), 1, Assoc::Left, binary("+"))
+                .infix(char('*'), 2, Assoc::Left, binary("*"))
+                .prefix(char('-'), 3, unary("-"))
+                .ternary(char('?'), char(':'), 0, |c, a, b| format!("({}?{}:{})", c, a, b))
+        });
+        let expression = Arc::new(expression);
+
+        let workers: Vec<_> = ["1+2*3", "-(a+b)*c", "x?y:z"]
+            .iter()
+            .map(|source| {
+                let expression = Arc::clone(&expression);
+                std::thread::spawn(move || expression.call(Text::new(source)).unwrap().0)
+            })
+            .collect();
+
+        let results: Vec<_> = workers.into_iter().map(|w| w.join().unwrap()).collect();
+        assert_eq!(vec!["(1+(2*3))", "((-(a+b))*c)", "(x?y:z)"], results);
+    }
+}
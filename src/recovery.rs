@@ -0,0 +1,358 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::{Expected, Input, ParseError, ParseResult, Parser, Position, Token};
+
+
+#[derive(Debug)]
+struct Recorded<E> {
+    error: E,
+    previous: Option<Arc<Recorded<E>>>,
+}
+
+
+/// Input that collects the errors recovered from along the way, which
+/// [`RecoverWith`] needs to run.
+///
+/// The errors travel with the input, so the input left after a parse holds
+/// every error on the path that was finally taken, and errors recovered
+/// from on a branch that was later backtracked out of are dropped along
+/// with it. If the parse fails regardless, only its final error is
+/// reported.
+#[derive(Clone, Debug)]
+pub struct Recovering<I: Input> {
+    input: I,
+    errors: Option<Arc<Recorded<<I as Input>::Error>>>,
+}
+
+
+impl<I: Input> Recovering<I> {
+    pub fn new(input: I) -> Self {
+        Self {
+            input,
+            errors: None
+        }
+    }
+
+    /// The wrapped input.
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Every error recovered from so far, in the order they happened.
+    pub fn errors(&self) -> Vec<<I as Input>::Error> {
+        let mut errors = Vec::new();
+        let mut next = self.errors.as_deref();
+        while let Some(recorded) = next {
+            errors.push(recorded.error.clone());
+            next = recorded.previous.as_deref();
+        }
+        errors.reverse();
+        errors
+    }
+
+    fn record(self, error: <I as Input>::Error) -> Self {
+        let recorded = Recorded {
+            error,
+            previous: self.errors
+        };
+        Self {
+            input: self.input,
+            errors: Some(Arc::new(recorded))
+        }
+    }
+}
+
+
+impl<I: Input> Input for Recovering<I> {
+    type Token = <I as Input>::Token;
+    type Slice = <I as Input>::Slice;
+    type Error = <I as Input>::Error;
+
+    fn next_token(&self) -> Option<(Self::Token, Self)> {
+        self.input.next_token().map(|(t, input)| {
+            let rest = Self {
+                input,
+                errors: self.errors.clone()
+            };
+            (t, rest)
+        })
+    }
+
+    fn slice_to(&self, end: &Self) -> Self::Slice {
+        self.input.slice_to(&end.input)
+    }
+
+    fn offset(&self) -> usize {
+        self.input.offset()
+    }
+
+    fn position(&self) -> Position {
+        self.input.position()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+}
+
+
+/// Recovers from a failure of `parser` by skipping input with `strategy`.
+///
+/// If `parser` fails, `strategy` is run from where `parser` started. When
+/// it matches and gets past that point, the error is recorded in the
+/// [`Recovering`] input, the default `Out` stands in for what `parser`
+/// would have produced, and parsing carries on after the skipped input.
+/// Otherwise the error is returned as usual. Fatal errors are recovered
+/// from as well, so a recovery point also ends the reach of a
+/// [`cut`](crate::cut).
+#[derive(Clone)]
+pub struct RecoverWith<P, R> {
+    parser: P,
+    strategy: R
+}
+
+
+impl<P, R> RecoverWith<P, R> {
+    pub fn new(parser: P, strategy: R) -> Self {
+        Self { parser, strategy }
+    }
+}
+
+
+impl<I, P, R> Parser<Recovering<I>> for RecoverWith<P, R>
+where
+    I: Input,
+    P: Parser<Recovering<I>>,
+    <P as Parser<Recovering<I>>>::Out: Default,
+    R: Parser<Recovering<I>>
+{
+    type Out = <P as Parser<Recovering<I>>>::Out;
+
+    fn call(&self, input: Recovering<I>) -> ParseResult<Recovering<I>, Self::Out> {
+        let err = match self.parser.call(input.clone()) {
+            Ok(ok) => return Ok(ok),
+            Err(e) => e,
+        };
+
+        match self.strategy.call(input.clone()) {
+            Ok((_, rest)) if rest.offset() > input.offset() => {
+                Ok((Default::default(), rest.record(err.into_recoverable())))
+            }
+            _ => Err(err),
+        }
+    }
+}
+
+
+/// Skips ahead to just past the next `token`, or to the end of the input
+/// if there is none. Meant as a strategy for [`RecoverWith`].
+pub struct SkipUntil<T, I> {
+    token: T,
+    phantom: PhantomData<I>
+}
+
+
+impl<T: Clone, I> Clone for SkipUntil<T, I> {
+    fn clone(&self) -> Self {
+        Self {
+            token: self.token.clone(),
+            phantom: PhantomData
+        }
+    }
+}
+
+
+impl<T: Copy, I> Copy for SkipUntil<T, I> {}
+
+
+impl<T: Token, I: Input<Token = T>> Parser<I> for SkipUntil<T, I> {
+    type Out = ();
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let mut rest = input;
+        while let Some((t, next)) = rest.next_token() {
+            rest = next;
+            if t == self.token {
+                break;
+            }
+        }
+        Ok(((), rest))
+    }
+}
+
+
+pub fn skip_until<T, I>(token: T) -> SkipUntil<T, I> {
+    SkipUntil { token, phantom: PhantomData }
+}
+
+
+/// Skips a whole group from `open` to its matching `close`, counting any
+/// groups nested inside. Meant as a strategy for [`RecoverWith`].
+///
+/// Fails if the input does not start with `open` or the group is never
+/// closed, in which case there is nothing sensible to skip to.
+pub struct DelimitedRecovery<T, I> {
+    open: T,
+    close: T,
+    phantom: PhantomData<I>
+}
+
+
+impl<T: Clone, I> Clone for DelimitedRecovery<T, I> {
+    fn clone(&self) -> Self {
+        Self {
+            open: self.open.clone(),
+            close: self.close.clone(),
+            phantom: PhantomData
+        }
+    }
+}
+
+
+impl<T: Copy, I> Copy for DelimitedRecovery<T, I> {}
+
+
+impl<T: Token, I: Input<Token = T>> Parser<I> for DelimitedRecovery<T, I> {
+    type Out = ();
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let mut rest = match input.next_token() {
+            Some((t, rest)) if t == self.open => rest,
+            _ => return Err(ParseError::from_unexpected(&input, Some(Expected::Token(self.open)))),
+        };
+
+        let mut depth = 1;
+        while depth > 0 {
+            let (t, next) = rest
+                .next_token()
+                .ok_or_else(|| ParseError::from_unexpected(&rest, Some(Expected::Token(self.close))))?;
+            if t == self.open {
+                depth += 1;
+            } else if t == self.close {
+                depth -= 1;
+            }
+            rest = next;
+        }
+        Ok(((), rest))
+    }
+}
+
+
+pub fn delimited_recovery<T, I>(open: T, close: T) -> DelimitedRecovery<T, I> {
+    DelimitedRecovery {
+        open,
+        close,
+        phantom: PhantomData
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{char, choice, cut, eof, satisfy, string, take_while1, Bytes, Text};
+
+    // statement = name '=' digit ';'
+    fn statement<'a>() -> impl Parser<Recovering<Text<'a>>, Out = (&'a str, char)> {
+        take_while1(|c| c.is_alphabetic())
+            .left(char('='))
+            .then(satisfy(|c| c.is_ascii_digit()))
+            .left(char(';'))
+    }
+
+    #[test]
+    fn recover_with_test() {
+        let (result, rest) =
+            statement()
+                .recover_with(skip_until(';'))
+                .many::<Vec<_>>()
+                .left(eof())
+                .call(Recovering::new(Text::new("a=1;b=;c=3;=4;")))
+                .unwrap();
+
+        assert_eq!(vec![("a", '1'), ("", '\0'), ("c", '3'), ("", '\0')], result);
+
+        let errors = rest.errors();
+        assert_eq!(2, errors.len());
+        assert_eq!(6, errors[0].offset());
+        assert_eq!(Some(';'), errors[0].found());
+        assert_eq!(11, errors[1].offset());
+    }
+
+    #[test]
+    fn recover_at_end_test() {
+        // Nothing left to skip, so the error stands.
+        let err =
+            statement()
+                .recover_with(skip_until(';'))
+                .call(Recovering::new(Text::new("")))
+                .unwrap_err();
+
+        assert_eq!(0, err.offset());
+
+        let (_, rest) =
+            statement()
+                .recover_with(skip_until(';'))
+                .call(Recovering::new(Text::new("a=")))
+                .unwrap();
+
+        assert!(rest.is_empty());
+        assert_eq!(1, rest.errors().len());
+    }
+
+    #[test]
+    fn delimited_recovery_test() {
+        let group = char('(').right(satisfy(|c| c.is_ascii_digit())).left(char(')'));
+
+        let (result, rest) =
+            group
+                .clone()
+                .recover_with(delimited_recovery('(', ')'))
+                .then(group.recover_with(delimited_recovery('(', ')')))
+                .call(Recovering::new(Text::new("((x)y)(2)")))
+                .unwrap();
+
+        assert_eq!(('\0', '2'), result);
+        assert_eq!(1, rest.errors()[0].offset());
+
+        assert!(delimited_recovery('(', ')').call(Text::new("(()")).is_err());
+        assert!(delimited_recovery('(', ')').call(Text::new("x")).is_err());
+    }
+
+    #[test]
+    fn backtracked_errors_test() {
+        // The first alternative recovers, but then fails as a whole, so
+        // its recorded error must not outlive it.
+        let first = string("ab").recover_with(skip_until('b')).left(char('!'));
+        let second = string("xb?");
+
+        let (result, rest) =
+            choice((first, second))
+                .call(Recovering::new(Text::new("xb?")))
+                .unwrap();
+
+        assert_eq!("xb?", result);
+        assert!(rest.errors().is_empty());
+    }
+
+    #[test]
+    fn recover_from_cut_test() {
+        let (_, rest) =
+            char('a')
+                .right(cut(char('b')))
+                .recover_with(skip_until(';'))
+                .call(Recovering::new(Text::new("ax;")))
+                .unwrap();
+
+        assert_eq!(1, rest.errors().len());
+        assert!(!rest.errors()[0].is_fatal());
+    }
+
+    #[test]
+    fn skip_until_bytes_test() {
+        let (_, rest) = skip_until(0u8).call(Bytes::new(&[1, 2, 0, 3])).unwrap();
+
+        assert_eq!(3, rest.offset());
+    }
+}
@@ -0,0 +1,195 @@
+use std::marker::PhantomData;
+use std::sync::{Arc, OnceLock, Weak};
+
+use crate::{BoxedParser, Input, ParseResult, Parser, SyncBoxedParser};
+
+
+// Ties a handle to the lifetime, input and output of its definition
+// without holding any of them, so it does not affect Send or Sync.
+type Marker<'a, I, O> = PhantomData<(&'a (), fn(I) -> O)>;
+
+
+enum Handle<B> {
+    Owned(Arc<OnceLock<B>>),
+    Weak(Weak<OnceLock<B>>),
+}
+
+
+/// A parser that can refer to itself, built with [`recursive`] or
+/// [`recursive_sync`].
+///
+/// A `Recursive` is a cheap, cloneable handle. The one handed to the
+/// definition closure only refers to the parser weakly, so a grammar that
+/// uses itself does not keep itself alive; the parser is freed once every
+/// handle returned from [`recursive`] is dropped. The definition is kept
+/// as a `B`, either a [`BoxedParser`] or a [`SyncBoxedParser`], and the
+/// handle can be shared between threads exactly when `B` can.
+pub struct Recursive<'a, I, O, B = BoxedParser<'a, I, O>> {
+    handle: Handle<B>,
+    phantom: Marker<'a, I, O>
+}
+
+
+impl<'a, I, O, B> Recursive<'a, I, O, B> {
+    fn new(handle: Handle<B>) -> Self {
+        Self { handle, phantom: PhantomData }
+    }
+}
+
+
+impl<'a, I, O, B> Clone for Recursive<'a, I, O, B> {
+    fn clone(&self) -> Self {
+        let handle = match &self.handle {
+            Handle::Owned(arc) => Handle::Owned(Arc::clone(arc)),
+            Handle::Weak(weak) => Handle::Weak(Weak::clone(weak)),
+        };
+        Self::new(handle)
+    }
+}
+
+
+impl<'a, I: Input, O, B: Parser<I, Out = O>> Parser<I> for Recursive<'a, I, O, B> {
+    type Out = O;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let definition = match &self.handle {
+            Handle::Owned(arc) => Arc::clone(arc),
+            Handle::Weak(weak) => weak
+                .upgrade()
+                .expect("recursive parser used after it was dropped"),
+        };
+        definition
+            .get()
+            .expect("recursive parser used before it was defined")
+            .call(input)
+    }
+}
+
+
+fn define_with<'a, I, O, B, P, F, E>(define: F, erase: E) -> Recursive<'a, I, O, B>
+where
+    F: FnOnce(Recursive<'a, I, O, B>) -> P,
+    E: FnOnce(P) -> B
+{
+    let definition = Arc::new(OnceLock::new());
+    let parser = define(Recursive::new(Handle::Weak(Arc::downgrade(&definition))));
+    // The cell was created empty just above, so this cannot fail.
+    let _ = definition.set(erase(parser));
+
+    Recursive::new(Handle::Owned(definition))
+}
+
+
+/// Builds a parser that can refer to itself, such as nested brackets or
+/// arrays of arrays.
+///
+/// `define` receives a handle to the parser being built and returns its
+/// definition, which may use the handle (or clones of it) anywhere a parser
+/// is expected, including inside other rules built in `define`, which is
+/// how mutually recursive rules are written. Running the handle inside
+/// `define` itself, before the definition exists, panics.
+pub fn recursive<'a, I, O, P, F>(define: F) -> Recursive<'a, I, O>
+where
+    I: Input,
+    P: Parser<I, Out = O> + 'a,
+    F: FnOnce(Recursive<'a, I, O>) -> P
+{
+    define_with(define, BoxedParser::new)
+}
+
+
+/// Like [`recursive`], for a `Send + Sync` definition, so that the parser
+/// can be shared between threads.
+pub fn recursive_sync<'a, I, O, P, F>(define: F) -> Recursive<'a, I, O, SyncBoxedParser<'a, I, O>>
+where
+    I: Input,
+    P: Parser<I, Out = O> + Send + Sync + 'a,
+    F: FnOnce(Recursive<'a, I, O, SyncBoxedParser<'a, I, O>>) -> P
+{
+    define_with(define, SyncBoxedParser::new)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{between, char, choice, satisfy, sep_by, sep_by1, Text};
+
+    #[derive(Debug, PartialEq)]
+    enum Tree {
+        Leaf,
+        Node(Vec<Tree>),
+    }
+
+    fn tree<'a>() -> Recursive<'a, Text<'a>, Tree> {
+        recursive(|tree| {
+            choice((
+                char('x').map(|_| Tree::Leaf),
+                between(char('['), sep_by(tree, char(',')), char(']')).map(Tree::Node),
+            ))
+        })
+    }
+
+    #[test]
+    fn recursive_test() {
+        let (result, rest) =
+            tree()
+                .call(Text::new("[x,[[x]],[]]!"))
+                .unwrap();
+
+        let expected = Tree::Node(vec![
+            Tree::Leaf,
+            Tree::Node(vec![Tree::Node(vec![Tree::Leaf])]),
+            Tree::Node(vec![]),
+        ]);
+        assert_eq!(expected, result);
+        assert_eq!("!", rest.as_str());
+    }
+
+    #[test]
+    fn recursive_error_test() {
+        let err = tree().call(Text::new("[x;")).unwrap_err();
+
+        assert_eq!(2, err.offset());
+        assert_eq!(Some(';'), err.found());
+    }
+
+    #[test]
+    fn mutually_recursive_test() {
+        // sum = term ('+' term)*, term = digit | '(' sum ')'
+        let sum = recursive(|sum| {
+            let digit = satisfy(|c| c.is_ascii_digit()).map(|c| c.to_digit(10).unwrap());
+            let term = choice((digit, between(char('('), sum, char(')'))));
+            sep_by1::<Vec<_>, _, _>(term, char('+')).map(|terms| terms.into_iter().sum())
+        });
+
+        let (result, rest) =
+            sum
+                .call(Text::new("1+(2+(3))+4;"))
+                .unwrap();
+
+        assert_eq!(10, result);
+        assert_eq!(";", rest.as_str());
+    }
+
+    #[test]
+    fn recursive_clone_test() {
+        let parser = tree();
+        let copy = parser.clone();
+        drop(parser);
+
+        assert!(copy.call(Text::new("[[x]]")).is_ok());
+    }
+
+    #[test]
+    fn recursive_drop_test() {
+        let parser = tree();
+        let definition = match &parser.handle {
+            Handle::Owned(arc) => Arc::downgrade(arc),
+            Handle::Weak(_) => unreachable!(),
+        };
+        drop(parser);
+
+        assert!(definition.upgrade().is_none());
+    }
+}
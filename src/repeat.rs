@@ -0,0 +1,650 @@
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+
+use crate::{ErrorKind, Input, ParseError, ParseResult, Parser};
+
+
+/// What a separated repetition does with a separator after the last item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Trailing {
+    /// Separators only go between items; a trailing one is left unconsumed.
+    Forbidden,
+    /// A trailing separator is consumed if there is one.
+    Optional,
+    /// Every item must be followed by a separator.
+    Required,
+}
+
+
+/// How many times a repetition may run its parser.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Bounds {
+    min: usize,
+    max: Option<usize>,
+}
+
+
+impl Bounds {
+    fn unbounded(min: usize) -> Self {
+        Self { min, max: None }
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `range` contains no counts at all, such as `5..2`.
+    fn from_range<R: RangeBounds<usize>>(range: R) -> Self {
+        let min = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n + 1,
+            Bound::Unbounded => 0,
+        };
+        let max = match range.end_bound() {
+            Bound::Included(&n) => Some(n),
+            Bound::Excluded(&n) => Some(n.checked_sub(1).expect("repetition range is empty")),
+            Bound::Unbounded => None,
+        };
+        if let Some(max) = max {
+            assert!(min <= max, "repetition range is empty");
+        }
+
+        Self { min, max }
+    }
+}
+
+
+/// Runs a parser over and over, yielding its outputs until it fails.
+///
+/// The iterator remembers why it stopped, so a repetition can hand the
+/// items straight to `FromIterator` and then decide whether the run as a
+/// whole succeeded.
+struct Repetition<'p, I: Input, P, S> {
+    parser: &'p P,
+    separator: Option<&'p S>,
+    trailing: Trailing,
+    max: Option<usize>,
+    rest: I,
+    count: usize,
+    failure: Option<<I as Input>::Error>,
+    stuck: Option<<I as Input>::Error>,
+    done: bool,
+}
+
+
+impl<'p, I: Input, P: Parser<I>, S: Parser<I>> Repetition<'p, I, P, S> {
+    fn new(
+        parser: &'p P,
+        separator: Option<&'p S>,
+        trailing: Trailing,
+        max: Option<usize>,
+        input: I
+    ) -> Self {
+        Self {
+            parser,
+            separator,
+            trailing,
+            max,
+            rest: input,
+            count: 0,
+            failure: None,
+            stuck: None,
+            done: false
+        }
+    }
+
+    fn step(&mut self) -> ParseResult<I, <P as Parser<I>>::Out> {
+        let mut start = self.rest.clone();
+        if let Some(sep) = self.separator {
+            if self.count > 0 && self.trailing != Trailing::Required {
+                start = sep.call(start)?.1;
+            }
+        }
+
+        let (item, mut rest) = match self.parser.call(start.clone()) {
+            Ok(ok) => ok,
+            Err(e) => {
+                if self.trailing == Trailing::Optional {
+                    self.rest = start;
+                }
+                return Err(e);
+            }
+        };
+
+        if let Some(sep) = self.separator {
+            if self.trailing == Trailing::Required {
+                rest = sep.call(rest)?.1;
+            }
+        }
+
+        Ok((item, rest))
+    }
+
+    /// Whether a step that ended at `rest` got nowhere, and so would keep
+    /// getting nowhere forever.
+    ///
+    /// The first item of a separated repetition may be empty, as in an
+    /// empty first field of `,b`: every later step needs a separator first,
+    /// so only those can go round in circles.
+    fn is_stuck(&self, rest: &I) -> bool {
+        let first_separated = self.separator.is_some() && self.count == 0;
+        rest.offset() == self.rest.offset() && !first_separated
+    }
+
+    /// Turns the collected items into the result of the whole repetition.
+    fn finish<C>(self, items: C, bounds: Bounds) -> ParseResult<I, C> {
+        if let Some(e) = self.stuck {
+            return Err(e);
+        }
+        match self.failure {
+            Some(e) if e.is_fatal() || self.count < bounds.min => Err(e),
+            _ => Ok((items, self.rest)),
+        }
+    }
+}
+
+
+impl<'p, I: Input, P: Parser<I>, S: Parser<I>> Iterator for Repetition<'p, I, P, S> {
+    type Item = <P as Parser<I>>::Out;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || Some(self.count) == self.max {
+            return None;
+        }
+
+        match self.step() {
+            Ok((_, rest)) if self.is_stuck(&rest) => {
+                // With an upper bound, running into it would end the loop,
+                // but only after as many useless steps as it allows. The
+                // repetition ends here instead, and only fails if it is
+                // short of its lower bound.
+                let error = ParseError::from_kind(&rest, ErrorKind::NoProgress);
+                if self.max.is_some() {
+                    self.failure = Some(error);
+                } else {
+                    self.stuck = Some(error);
+                }
+                self.done = true;
+                None
+            }
+            Ok((item, rest)) => {
+                self.rest = rest;
+                self.count += 1;
+                Some(item)
+            }
+            Err(e) => {
+                self.failure = Some(e);
+                self.done = true;
+                None
+            }
+        }
+    }
+}
+
+
+/// Repeats a parser as long as it succeeds and collects the outputs.
+///
+/// Fails if the parser succeeded fewer than `min` times, or if it ever
+/// succeeds without consuming input.
+pub struct Many<P, C> {
+    parser: P,
+    bounds: Bounds,
+    container: PhantomData<C>
+}
+
+
+impl<P: Clone, C> Clone for Many<P, C> {
+    fn clone(&self) -> Self {
+        Self {
+            parser: self.parser.clone(),
+            bounds: self.bounds,
+            container: PhantomData
+        }
+    }
+}
+
+
+impl<P, C> Many<P, C> {
+    pub fn new(parser: P, min: usize) -> Self {
+        Self {
+            parser,
+            bounds: Bounds::unbounded(min),
+            container: PhantomData
+        }
+    }
+}
+
+
+impl<I: Input, P, C> Parser<I> for Many<P, C>
+where
+    P: Parser<I>,
+    C: FromIterator<<P as Parser<I>>::Out>
+{
+    type Out = C;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let mut items = Repetition::new(&self.parser, None::<&P>, Trailing::Forbidden, None, input);
+        let out = items.by_ref().collect();
+        items.finish(out, self.bounds)
+    }
+}
+
+
+/// Repeats a parser with a separator between the items and collects the
+/// outputs, dropping the separators.
+///
+/// If a separator matches but the item after it does not, the repetition
+/// ends before that separator.
+pub struct SepBy<P, S, C> {
+    parser: P,
+    separator: S,
+    bounds: Bounds,
+    trailing: Trailing,
+    container: PhantomData<C>
+}
+
+
+impl<P: Clone, S: Clone, C> Clone for SepBy<P, S, C> {
+    fn clone(&self) -> Self {
+        Self {
+            parser: self.parser.clone(),
+            separator: self.separator.clone(),
+            bounds: self.bounds,
+            trailing: self.trailing,
+            container: PhantomData
+        }
+    }
+}
+
+
+impl<P, S, C> SepBy<P, S, C> {
+    fn new(parser: P, separator: S, min: usize, trailing: Trailing) -> Self {
+        Self {
+            parser,
+            separator,
+            bounds: Bounds::unbounded(min),
+            trailing,
+            container: PhantomData
+        }
+    }
+}
+
+
+impl<I: Input, P, S, C> Parser<I> for SepBy<P, S, C>
+where
+    P: Parser<I>,
+    S: Parser<I>,
+    C: FromIterator<<P as Parser<I>>::Out>
+{
+    type Out = C;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let mut items = Repetition::new(&self.parser, Some(&self.separator), self.trailing, None, input);
+        let out = items.by_ref().collect();
+        items.finish(out, self.bounds)
+    }
+}
+
+
+/// Zero or more `parser`s separated by `separator`.
+pub fn sep_by<C, P, S>(parser: P, separator: S) -> SepBy<P, S, C> {
+    SepBy::new(parser, separator, 0, Trailing::Forbidden)
+}
+
+
+/// One or more `parser`s separated by `separator`.
+pub fn sep_by1<C, P, S>(parser: P, separator: S) -> SepBy<P, S, C> {
+    SepBy::new(parser, separator, 1, Trailing::Forbidden)
+}
+
+
+/// Zero or more `parser`s separated, and optionally ended, by `separator`.
+pub fn sep_end_by<C, P, S>(parser: P, separator: S) -> SepBy<P, S, C> {
+    SepBy::new(parser, separator, 0, Trailing::Optional)
+}
+
+
+/// Zero or more `parser`s, each one ended by `separator`.
+pub fn end_by<C, P, S>(parser: P, separator: S) -> SepBy<P, S, C> {
+    SepBy::new(parser, separator, 0, Trailing::Required)
+}
+
+
+/// Repeats a parser a number of times within a range and collects the
+/// outputs.
+///
+/// Stops as soon as the upper bound is reached, and fails if the parser
+/// succeeded fewer times than the lower bound. If the parser succeeds
+/// without consuming input, the repetition stops before that item when
+/// there is an upper bound, which still fails if the lower bound has not
+/// been reached, and fails outright without one, like [`Many`].
+pub struct Repeat<P, C> {
+    parser: P,
+    bounds: Bounds,
+    container: PhantomData<C>
+}
+
+
+impl<P: Clone, C> Clone for Repeat<P, C> {
+    fn clone(&self) -> Self {
+        Self {
+            parser: self.parser.clone(),
+            bounds: self.bounds,
+            container: PhantomData
+        }
+    }
+}
+
+
+impl<I: Input, P, C> Parser<I> for Repeat<P, C>
+where
+    P: Parser<I>,
+    C: FromIterator<<P as Parser<I>>::Out>
+{
+    type Out = C;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let mut items = Repetition::new(&self.parser, None::<&P>, Trailing::Forbidden, self.bounds.max, input);
+        let out = items.by_ref().collect();
+        items.finish(out, self.bounds)
+    }
+}
+
+
+/// Between `range.start` and `range.end` repetitions of `parser`, e.g.
+/// `repeat(hex_digit, 2..=4)`.
+///
+/// # Panics
+///
+/// Panics if `range` is empty.
+pub fn repeat<C, P, R: RangeBounds<usize>>(parser: P, range: R) -> Repeat<P, C> {
+    Repeat {
+        parser,
+        bounds: Bounds::from_range(range),
+        container: PhantomData
+    }
+}
+
+
+/// `n` or more repetitions of `parser`.
+pub fn at_least<C, P>(parser: P, n: usize) -> Repeat<P, C> {
+    repeat(parser, n..)
+}
+
+
+/// Up to `n` repetitions of `parser`.
+pub fn at_most<C, P>(parser: P, n: usize) -> Repeat<P, C> {
+    repeat(parser, ..=n)
+}
+
+
+/// Like [`Repeat`], but folds the outputs into an accumulator instead of
+/// collecting them.
+#[derive(Clone)]
+pub struct RepeatFold<P, A, F> {
+    parser: P,
+    bounds: Bounds,
+    init: A,
+    func: F
+}
+
+
+impl<I: Input, P, A, F> Parser<I> for RepeatFold<P, A, F>
+where
+    P: Parser<I>,
+    A: Clone,
+    F: Fn(A, <P as Parser<I>>::Out) -> A
+{
+    type Out = A;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let mut items = Repetition::new(&self.parser, None::<&P>, Trailing::Forbidden, self.bounds.max, input);
+        let out = items.by_ref().fold(self.init.clone(), &self.func);
+        items.finish(out, self.bounds)
+    }
+}
+
+
+/// Folds between `range.start` and `range.end` repetitions of `parser`,
+/// starting from a clone of `init`.
+///
+/// # Panics
+///
+/// Panics if `range` is empty.
+pub fn repeat_fold<I: Input, P, R, A, F>(parser: P, range: R, init: A, func: F) -> RepeatFold<P, A, F>
+where
+    P: Parser<I>,
+    R: RangeBounds<usize>,
+    A: Clone,
+    F: Fn(A, <P as Parser<I>>::Out) -> A
+{
+    RepeatFold {
+        parser,
+        bounds: Bounds::from_range(range),
+        init,
+        func
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{char, pure, take_while, ErrorKind, Item, Position, Return, Text};
+
+    #[test]
+    fn many_test() {
+        let (result, rest) =
+            char('a')
+                .many::<String>()
+                .call(Text::new("aaab"))
+                .unwrap();
+
+        assert_eq!("aaa", result);
+        assert_eq!("b", rest.as_str());
+    }
+
+    #[test]
+    fn many_empty_test() {
+        let (result, rest) =
+            char('a')
+                .many::<Vec<_>>()
+                .call(Text::new("b"))
+                .unwrap();
+
+        assert!(result.is_empty());
+        assert_eq!("b", rest.as_str());
+    }
+
+    #[test]
+    fn many1_test() {
+        let err =
+            char('a')
+                .many1::<Vec<_>>()
+                .call(Text::new("b"))
+                .unwrap_err();
+
+        assert_eq!(Some('b'), err.found());
+
+        let (result, _) =
+            char('a')
+                .many1::<Vec<_>>()
+                .call(Text::new("ab"))
+                .unwrap();
+
+        assert_eq!(vec!['a'], result);
+    }
+
+    #[test]
+    fn many_no_progress_test() {
+        let err =
+            Return::new(1)
+                .many::<Vec<_>>()
+                .call(Text::new("abc"))
+                .unwrap_err();
+
+        assert_eq!(ErrorKind::NoProgress, err.kind());
+        assert_eq!(Position::default(), err.position());
+    }
+
+    #[test]
+    fn sep_by_test() {
+        let (result, rest) =
+            sep_by::<Vec<_>, _, _>(Item::new(), char(','))
+                .call(Text::new("a,b,c,"))
+                .unwrap();
+
+        assert_eq!(vec!['a', 'b', 'c'], result);
+        assert_eq!(",", rest.as_str());
+    }
+
+    #[test]
+    fn sep_by_empty_item_test() {
+        let fields = || sep_by::<Vec<_>, _, _>(take_while(|c| c != ','), char(','));
+
+        let (result, rest) = fields().call(Text::new(",b")).unwrap();
+        assert_eq!(vec!["", "b"], result);
+        assert!(rest.is_empty());
+
+        let (result, _) = fields().call(Text::new("")).unwrap();
+        assert_eq!(vec![""], result);
+
+        let (result, _) = fields().call(Text::new("a,,b")).unwrap();
+        assert_eq!(vec!["a", "", "b"], result);
+
+        let err =
+            sep_by::<Vec<_>, _, _>(Return::new(1), Return::new(()))
+                .call(Text::new("a"))
+                .unwrap_err();
+
+        assert_eq!(ErrorKind::NoProgress, err.kind());
+    }
+
+    #[test]
+    fn sep_by1_test() {
+        let err =
+            sep_by1::<Vec<_>, _, _>(char('a'), char(','))
+                .call(Text::new(""))
+                .unwrap_err();
+
+        assert_eq!(None, err.found());
+    }
+
+    #[test]
+    fn sep_end_by_test() {
+        let (result, rest) =
+            sep_end_by::<Vec<_>, _, _>(char('a'), char(';'))
+                .call(Text::new("a;a;b"))
+                .unwrap();
+
+        assert_eq!(vec!['a', 'a'], result);
+        assert_eq!("b", rest.as_str());
+
+        let (result, rest) =
+            sep_end_by::<Vec<_>, _, _>(char('a'), char(';'))
+                .call(Text::new("a;ab"))
+                .unwrap();
+
+        assert_eq!(vec!['a', 'a'], result);
+        assert_eq!("b", rest.as_str());
+    }
+
+    #[test]
+    fn end_by_test() {
+        let (result, rest) =
+            end_by::<String, _, _>(char('a'), char(';'))
+                .call(Text::new("a;a;a"))
+                .unwrap();
+
+        assert_eq!("aa", result);
+        assert_eq!("a", rest.as_str());
+    }
+
+    #[test]
+    fn repeat_test() {
+        let (result, rest) =
+            repeat::<String, _, _>(char('a'), 2..=3)
+                .call(Text::new("aaaa"))
+                .unwrap();
+
+        assert_eq!("aaa", result);
+        assert_eq!("a", rest.as_str());
+
+        let err =
+            repeat::<String, _, _>(char('a'), 2..4)
+                .call(Text::new("ab"))
+                .unwrap_err();
+
+        assert_eq!(Position::new(1, 1, 2), err.position());
+    }
+
+    #[test]
+    fn at_least_at_most_test() {
+        let (result, _) =
+            at_least::<Vec<_>, _>(char('a'), 1)
+                .call(Text::new("aaab"))
+                .unwrap();
+
+        assert_eq!(3, result.len());
+
+        let (result, rest) =
+            at_most::<Vec<_>, _>(char('a'), 2)
+                .call(Text::new("aaab"))
+                .unwrap();
+
+        assert_eq!(2, result.len());
+        assert_eq!("ab", rest.as_str());
+    }
+
+    #[test]
+    fn repeat_bounded_no_progress_test() {
+        let (result, rest) =
+            repeat::<String, _, _>(char('a').or(pure('x')), ..=3)
+                .call(Text::new("ab"))
+                .unwrap();
+
+        assert_eq!("a", result);
+        assert_eq!("b", rest.as_str());
+
+        let (result, _) =
+            at_most::<Vec<_>, _>(pure('x'), usize::MAX)
+                .call(Text::new("ab"))
+                .unwrap();
+
+        assert!(result.is_empty());
+
+        let err =
+            repeat::<Vec<_>, _, _>(char('a').or(pure('x')), 2..=1_000_000_000)
+                .call(Text::new("ab"))
+                .unwrap_err();
+
+        assert_eq!(ErrorKind::NoProgress, err.kind());
+        assert_eq!(1, err.offset());
+
+        let err =
+            at_least::<Vec<_>, _>(pure('x'), 1)
+                .call(Text::new("ab"))
+                .unwrap_err();
+
+        assert_eq!(ErrorKind::NoProgress, err.kind());
+    }
+
+    #[test]
+    fn repeat_fold_test() {
+        let hex = Item::new().map(|c: char| c.to_digit(16).unwrap());
+
+        let (result, rest) =
+            repeat_fold(hex, 2..=4, 0, |acc, d| acc * 16 + d)
+                .call(Text::new("ff"))
+                .unwrap();
+
+        assert_eq!(255, result);
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "repetition range is empty")]
+    fn repeat_empty_range_test() {
+        let (start, end) = (3, 2);
+        repeat::<Vec<char>, _, _>(Item::<Text>::new(), start..end);
+    }
+}
@@ -0,0 +1,190 @@
+use std::fmt;
+
+use crate::{DetailedError, ErrorKind};
+
+
+// Escape codes to wrap each part of a report in.
+struct Style {
+    error: &'static str,
+    accent: &'static str,
+    note: &'static str,
+    reset: &'static str,
+}
+
+
+const PLAIN: Style = Style {
+    error: "",
+    accent: "",
+    note: "",
+    reset: ""
+};
+
+
+const ANSI: Style = Style {
+    error: "\x1b[1;31m",
+    accent: "\x1b[1;34m",
+    note: "\x1b[1m",
+    reset: "\x1b[0m"
+};
+
+
+/// A [`DetailedError`] on text laid out for people to read, showing the
+/// offending source line with a caret under the failure:
+///
+/// ```text
+/// error: expected ')', found ';'
+///  --> main.fn:1:9
+///   |
+/// 1 | fn main(;
+///   |         ^ expected ')'
+///   = while parsing function
+/// ```
+///
+/// The expected set goes next to the caret and the context chain follows
+/// it, innermost first. Built with [`DetailedError::report`]; `source` must
+/// be the text that was parsed.
+pub struct Report<'a> {
+    error: &'a DetailedError<char>,
+    source: &'a str,
+    name: Option<&'a str>,
+    ansi: bool,
+}
+
+
+impl<'a> Report<'a> {
+    pub fn new(error: &'a DetailedError<char>, source: &'a str) -> Self {
+        Self {
+            error,
+            source,
+            name: None,
+            ansi: false
+        }
+    }
+
+    /// Names the source, typically after its file, in the location line.
+    pub fn name(mut self, name: &'a str) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Colors the report with ANSI escape codes, for terminals.
+    pub fn ansi(mut self) -> Self {
+        self.ansi = true;
+        self
+    }
+}
+
+
+impl<'a> fmt::Display for Report<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let style = if self.ansi { &ANSI } else { &PLAIN };
+        let error = self.error;
+        let position = error.position();
+
+        // The line the error is on, found by offset so that it cannot
+        // disagree with the position about where that is.
+        let offset = position.offset.min(self.source.len());
+        let start = self.source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let end = self.source[offset..].find('\n').map_or(self.source.len(), |i| offset + i);
+        let line = self.source[start..end].trim_end_matches('\r');
+        let before = &self.source[start..offset];
+
+        let number = position.line.to_string();
+        let gutter = " ".repeat(number.len());
+
+        write!(f, "{}error{}: ", style.error, style.reset)?;
+        error.fmt_message(f)?;
+        writeln!(f)?;
+
+        write!(f, "{}{}-->{} ", gutter, style.accent, style.reset)?;
+        if let Some(name) = self.name {
+            write!(f, "{}:", name)?;
+        }
+        writeln!(f, "{}", position)?;
+
+        writeln!(f, "{} {}|{}", gutter, style.accent, style.reset)?;
+        writeln!(f, "{}{} |{} {}", style.accent, number, style.reset, line)?;
+
+        // Tabs are kept so that the caret lines up however wide they are
+        // shown.
+        let indent: String = before.chars().map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+        write!(f, "{} {}|{} {}{}^", gutter, style.accent, style.reset, indent, style.error)?;
+        if error.kind() == ErrorKind::Unexpected && !error.expected().is_empty() {
+            write!(f, " ")?;
+            error.fmt_expected(f)?;
+        }
+        writeln!(f, "{}", style.reset)?;
+
+        for context in error.context() {
+            writeln!(f, "{} {}={} {}", gutter, style.note, style.reset, context)?;
+        }
+        Ok(())
+    }
+}
+
+
+impl DetailedError<char> {
+    /// Lays the error out against the `source` it came from; see
+    /// [`Report`].
+    pub fn report<'a>(&'a self, source: &'a str) -> Report<'a> {
+        Report::new(self, source)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{char, string, take_while1, Expected, Parser, Position, Text};
+
+    #[test]
+    fn report_test() {
+        let source = "fn main(;";
+        let err =
+            string("fn ")
+                .right(take_while1(|c| c.is_alphabetic()))
+                .left(char('('))
+                .left(char(')'))
+                .context("while parsing function")
+                .call(Text::new(source))
+                .unwrap_err();
+
+        let expected = "\
+error: expected ')', found ';'
+ --> main.fn:1:9
+  |
+1 | fn main(;
+  |         ^ expected ')'
+  = while parsing function
+";
+        assert_eq!(expected, err.report(source).name("main.fn").to_string());
+    }
+
+    #[test]
+    fn report_later_line_test() {
+        let source = "a\n\tb\r\nc";
+        let err = DetailedError::new(Position::new(4, 2, 3), vec![], Some('\r'));
+
+        let expected = "\
+error: unexpected '\\r'
+ --> 2:3
+  |
+2 | \tb
+  | \t ^
+";
+        assert_eq!(expected, err.report(source).to_string());
+
+        let err = DetailedError::new(Position::new(57, 12, 1), vec![Expected::EndOfInput], Some('x'));
+        assert!(err.report(&"\n".repeat(11)).to_string().contains("12 | \n   |"));
+    }
+
+    #[test]
+    fn report_ansi_test() {
+        let err = DetailedError::new(Position::default(), vec![Expected::Token('a')], None);
+
+        let report = err.report("").ansi().to_string();
+
+        assert!(report.starts_with("\x1b[1;31merror\x1b[0m: expected 'a', found end of input\n"));
+        assert!(report.contains("\x1b[1;31m^ expected 'a'\x1b[0m"));
+    }
+}
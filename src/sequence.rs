@@ -0,0 +1,224 @@
+use crate::{Input, ParseResult, Parser};
+
+
+/// Runs `first` and then `second`, keeping both outputs.
+#[derive(Clone)]
+pub struct Then<A, B> {
+    pub(crate) first: A,
+    pub(crate) second: B
+}
+
+
+impl<A, B> Then<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second
+        }
+    }
+}
+
+
+impl<I: Input, A: Parser<I>, B: Parser<I>> Parser<I> for Then<A, B> {
+    type Out = (<A as Parser<I>>::Out, <B as Parser<I>>::Out);
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let (a, rest) = self.first.call(input)?;
+        let (b, rest) = self.second.call(rest)?;
+        Ok(((a, b), rest))
+    }
+}
+
+
+pub fn pair<I: Input, A: Parser<I>, B: Parser<I>>(first: A, second: B) -> Then<A, B> {
+    Then::new(first, second)
+}
+
+
+/// Runs `first` and then `second`, keeping only the output of `first`.
+#[derive(Clone)]
+pub struct Left<A, B> {
+    pub(crate) first: A,
+    pub(crate) second: B
+}
+
+
+impl<A, B> Left<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second
+        }
+    }
+}
+
+
+impl<I: Input, A: Parser<I>, B: Parser<I>> Parser<I> for Left<A, B> {
+    type Out = <A as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let (a, rest) = self.first.call(input)?;
+        let (_, rest) = self.second.call(rest)?;
+        Ok((a, rest))
+    }
+}
+
+
+/// Runs `first` and then `second`, keeping only the output of `second`.
+#[derive(Clone)]
+pub struct Right<A, B> {
+    pub(crate) first: A,
+    pub(crate) second: B
+}
+
+
+impl<A, B> Right<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second
+        }
+    }
+}
+
+
+impl<I: Input, A: Parser<I>, B: Parser<I>> Parser<I> for Right<A, B> {
+    type Out = <B as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let (_, rest) = self.first.call(input)?;
+        self.second.call(rest)
+    }
+}
+
+
+/// Runs `open`, `parser` and `close`, keeping only the output of `parser`.
+#[derive(Clone)]
+pub struct Between<O, P, C> {
+    open: O,
+    parser: P,
+    close: C
+}
+
+
+impl<O, P, C> Between<O, P, C> {
+    pub fn new(open: O, parser: P, close: C) -> Self {
+        Self {
+            open,
+            parser,
+            close
+        }
+    }
+}
+
+
+impl<I: Input, O: Parser<I>, P: Parser<I>, C: Parser<I>> Parser<I> for Between<O, P, C> {
+    type Out = <P as Parser<I>>::Out;
+
+    fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+        let (_, rest) = self.open.call(input)?;
+        let (p, rest) = self.parser.call(rest)?;
+        let (_, rest) = self.close.call(rest)?;
+        Ok((p, rest))
+    }
+}
+
+
+pub fn between<I: Input, O: Parser<I>, P: Parser<I>, C: Parser<I>>(open: O, parser: P, close: C) -> Between<O, P, C> {
+    Between::new(open, parser, close)
+}
+
+
+// A tuple of parsers runs them one after the other and collects their
+// outputs into a tuple of the same shape.
+macro_rules! sequence_tuple {
+    ($($name:ident)+) => {
+        impl<I: Input, $($name: Parser<I>),+> Parser<I> for ($($name,)+) {
+            type Out = ($(<$name as Parser<I>>::Out,)+);
+
+            #[allow(non_snake_case)]
+            fn call(&self, input: I) -> ParseResult<I, Self::Out> {
+                let ($($name,)+) = self;
+                let rest = input;
+                $(
+                    let ($name, rest) = $name.call(rest)?;
+                )+
+                Ok((($($name,)+), rest))
+            }
+        }
+    };
+}
+
+
+sequence_tuple!(P1);
+sequence_tuple!(P1 P2);
+sequence_tuple!(P1 P2 P3);
+sequence_tuple!(P1 P2 P3 P4);
+sequence_tuple!(P1 P2 P3 P4 P5);
+sequence_tuple!(P1 P2 P3 P4 P5 P6);
+sequence_tuple!(P1 P2 P3 P4 P5 P6 P7);
+sequence_tuple!(P1 P2 P3 P4 P5 P6 P7 P8);
+sequence_tuple!(P1 P2 P3 P4 P5 P6 P7 P8 P9);
+sequence_tuple!(P1 P2 P3 P4 P5 P6 P7 P8 P9 P10);
+sequence_tuple!(P1 P2 P3 P4 P5 P6 P7 P8 P9 P10 P11);
+sequence_tuple!(P1 P2 P3 P4 P5 P6 P7 P8 P9 P10 P11 P12);
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Item, Position, Text, Zero};
+
+    #[test]
+    fn then_test() {
+        let (result, rest) =
+            Item::new()
+                .then(Item::new().take(2))
+                .call(Text::new("abcd"))
+                .unwrap();
+
+        assert_eq!(('a', vec!['b', 'c']), result);
+        assert_eq!("d", rest.as_str());
+    }
+
+    #[test]
+    fn left_right_test() {
+        let (left, _) = Item::new().left(Item::new()).call(Text::new("ab")).unwrap();
+        let (right, _) = Item::new().right(Item::new()).call(Text::new("ab")).unwrap();
+
+        assert_eq!('a', left);
+        assert_eq!('b', right);
+    }
+
+    #[test]
+    fn between_test() {
+        let (result, rest) =
+            between(Item::new(), Item::new().take(2), Item::new())
+                .call(Text::new("(ab)!"))
+                .unwrap();
+
+        assert_eq!(vec!['a', 'b'], result);
+        assert_eq!("!", rest.as_str());
+    }
+
+    #[test]
+    fn tuple_test() {
+        let (result, rest) =
+            (Item::new(), Item::new().take(2), Item::new().map(|c: char| c.to_ascii_uppercase()))
+                .call(Text::new("abcde"))
+                .unwrap();
+
+        assert_eq!(('a', vec!['b', 'c'], 'D'), result);
+        assert_eq!("e", rest.as_str());
+    }
+
+    #[test]
+    fn tuple_error_test() {
+        let err =
+            (Item::new(), Item::new(), Zero::<(), _>::new())
+                .call(Text::new("abc"))
+                .unwrap_err();
+
+        assert_eq!(Position::new(2, 1, 3), err.position());
+    }
+}